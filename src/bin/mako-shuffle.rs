@@ -0,0 +1,388 @@
+//! Shuffles and enumerates spaces made from lines of text, in the same deterministic orders the library produces, so that shell scripts can agree with Rust code about what comes where.
+//! ```text
+//! mako-shuffle --seed 7 names.txt                  # shuffle lines
+//! mako-shuffle cross --ordered sizes.txt colors.txt # every size with every color
+//! mako-shuffle subsets 2 --shard 3/8 < players.txt  # the 4th of 8 shards of the shuffled pairs
+//! mako-shuffle --seed 7 --position-of alice names.txt
+//! ```
+
+use std::{
+    fs,
+    io::{self, BufWriter, Read, Write},
+    process::exit,
+    str::FromStr,
+};
+
+use mako_infinite_shuffle::{
+    rng::{self, ShufflerConfig},
+    CrossVec, Indexing, InverseIndexing, KSubmultisets, KSubsets, OpsRef, Permutations, Shard,
+    Shuffled, Window,
+};
+
+const USAGE: &str = "\
+usage: mako-shuffle [MODE] [OPTIONS] [FILE...]
+
+Reads lines from the FILEs (or stdin, or `-`) and prints the items of a space built from them, one per line, in a seeded shuffled order.
+
+modes:
+  shuffle          each line on its own (the default)
+  cross            one line from each FILE, every combination
+  subsets K        every set of K distinct lines
+  multisets K      every set of K lines, repeats allowed
+  permutations     every ordering of all of the lines
+
+options:
+  --seed N             which shuffled order (default 0)
+  --shuffler NAME      lfsrf or lfsrf_n_times (default lfsrf_n_times)
+  --rounds N           rounds for lfsrf_n_times (default 11)
+  --ordered            don't shuffle
+  --range A..B         only positions A to B of the order
+  --shard I/N          only the Ith of N strided shards (after --range)
+  --at N               print only the item at position N
+  --position-of ITEM   print the position of ITEM, whose lines are joined with the separator
+  --separator S        joins the lines of an item (default tab)
+";
+
+enum Mode {
+    Shuffle,
+    Cross,
+    Subsets(usize),
+    Multisets(usize),
+    Permutations,
+}
+
+struct Args {
+    mode: Mode,
+    config: ShufflerConfig,
+    ordered: bool,
+    range: Option<(usize, usize)>,
+    shard: Option<(usize, usize)>,
+    at: Option<usize>,
+    position_of: Option<String>,
+    separator: String,
+    files: Vec<String>,
+}
+
+/// why a run stopped early
+enum Failure {
+    /// reported, exits 2
+    Message(String),
+    /// `--position-of` found nothing, exits 1 quietly
+    NotFound,
+    Io(io::Error),
+}
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::Io(e)
+    }
+}
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("mako-shuffle: {message}");
+    exit(2)
+}
+
+fn number<T: FromStr>(flag: &str, s: &str) -> Result<T, String> {
+    s.parse()
+        .map_err(|_| format!("{flag} expects a number, got {s:?}"))
+}
+
+fn pair(flag: &str, s: &str, sep: &str) -> Result<(usize, usize), String> {
+    match s.split_once(sep) {
+        Some((a, b)) => Ok((number(flag, a)?, number(flag, b)?)),
+        None => Err(format!("{flag} expects A{sep}B, got {s:?}")),
+    }
+}
+
+/// None if help was asked for
+fn parse_args(mut raw: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut args = Args {
+        mode: Mode::Shuffle,
+        config: ShufflerConfig::default(),
+        ordered: false,
+        range: None,
+        shard: None,
+        at: None,
+        position_of: None,
+        separator: "\t".into(),
+        files: Vec::new(),
+    };
+    let mut first = true;
+    while let Some(a) = raw.next() {
+        let mut value = || raw.next().ok_or_else(|| format!("{a} needs a value"));
+        match a.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--seed" => args.config.seed = number(&a, &value()?)?,
+            "--shuffler" => args.config.kind = value()?.parse()?,
+            "--rounds" => args.config.rounds = number(&a, &value()?)?,
+            "--ordered" => args.ordered = true,
+            "--range" => args.range = Some(pair(&a, &value()?, "..")?),
+            "--shard" => args.shard = Some(pair(&a, &value()?, "/")?),
+            "--at" => args.at = Some(number(&a, &value()?)?),
+            "--position-of" => args.position_of = Some(value()?),
+            "--separator" => args.separator = value()?,
+            "shuffle" if first => args.mode = Mode::Shuffle,
+            "cross" if first => args.mode = Mode::Cross,
+            "subsets" if first => args.mode = Mode::Subsets(number(&a, &value()?)?),
+            "multisets" if first => args.mode = Mode::Multisets(number(&a, &value()?)?),
+            "permutations" if first => args.mode = Mode::Permutations,
+            _ if a.starts_with("--") => return Err(format!("unknown option {a}\n\n{USAGE}")),
+            _ => args.files.push(a),
+        }
+        first = false;
+    }
+    if let Some((i, n)) = args.shard {
+        if i >= n {
+            return Err(format!("shard {i} of {n} doesn't exist"));
+        }
+    }
+    Ok(Some(args))
+}
+
+fn read_lines(file: &str) -> Vec<String> {
+    let mut text = String::new();
+    let read = if file == "-" {
+        io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        fs::read_to_string(file).map(|t| text = t)
+    };
+    read.unwrap_or_else(|e| fail(format_args!("couldn't read {file}: {e}")));
+    text.lines().map(String::from).collect()
+}
+
+/// n choose k, or None if it doesn't fit. Each step is exact, since the running product is itself a binomial.
+fn combinations(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+    (0..k.min(n - k)).try_fold(1usize, |c, i| {
+        (c as u128 * (n - i) as u128 / (i as u128 + 1))
+            .try_into()
+            .ok()
+    })
+}
+
+/// the number of items in the space, or why it can't be counted. This repeats the library's `len` arithmetic with checks on purpose: a Rust caller picks sizes it knows are sane and shouldn't pay for a `Result` on every `len`, while here the size comes from whatever got piped in, so it's guarded at the edge rather than in the library.
+fn space_size(mode: &Mode, axes: &[Vec<String>]) -> Result<usize, String> {
+    let n = axes[0].len();
+    let too_big = || "the space has more items than can be counted".to_string();
+    // combinations are counted through k!, which overflows past 20
+    let choose = |n: usize, k: usize| {
+        if k > 20 && k <= n {
+            return Err(format!("can't choose more than 20 lines at once, got {k}"));
+        }
+        combinations(n, k).ok_or_else(too_big)
+    };
+    match *mode {
+        Mode::Shuffle => Ok(n),
+        Mode::Cross => axes
+            .iter()
+            .try_fold(1usize, |p, a| p.checked_mul(a.len()))
+            .ok_or_else(too_big),
+        Mode::Subsets(k) => choose(n, k),
+        Mode::Multisets(k) => match (n + k).checked_sub(1) {
+            Some(m) => choose(m, k),
+            None => Err("there are no lines to make multisets from".into()),
+        },
+        Mode::Permutations => (1..=n)
+            .try_fold(1usize, |p, i| p.checked_mul(i))
+            .ok_or_else(too_big),
+    }
+}
+
+/// every mode's items are lists of line indices, into the axis files for cross, and into all the lines for the rest
+fn build(
+    args: &Args,
+    axes: &[Vec<String>],
+) -> Result<Box<dyn InverseIndexing<Item = Vec<usize>>>, String> {
+    let size = space_size(&args.mode, axes)?;
+    if !args.ordered && size > rng::MAX_LENGTH {
+        return Err(format!(
+            "can't shuffle {size} items, the shufflers go up to {}; --ordered lists them in order",
+            rng::MAX_LENGTH
+        ));
+    }
+    let n = axes[0].len();
+    let base: Box<dyn InverseIndexing<Item = Vec<usize>>> = match args.mode {
+        Mode::Shuffle => Box::new(KSubsets::new(n, 1)),
+        Mode::Cross => Box::new(CrossVec(axes.iter().map(|a| 0..a.len()).collect())),
+        Mode::Subsets(k) => Box::new(KSubsets::new(n, k)),
+        Mode::Multisets(k) => Box::new(KSubmultisets::new(n, k)),
+        Mode::Permutations => Box::new(Permutations::new(n)),
+    };
+    let ordered: Box<dyn InverseIndexing<Item = Vec<usize>>> = if args.ordered {
+        base
+    } else {
+        Box::new(Shuffled::with_config(base, &args.config))
+    };
+    let ranged: Box<dyn InverseIndexing<Item = Vec<usize>>> = match args.range {
+        Some((a, b)) => Box::new(Window(a..b, ordered)),
+        None => ordered,
+    };
+    Ok(match args.shard {
+        Some((i, count)) => Box::new(Shard::new(ranged, i, count)),
+        None => ranged,
+    })
+}
+
+fn run(args: &Args, axes: &[Vec<String>], out: &mut impl Write) -> Result<(), Failure> {
+    let space = build(args, axes).map_err(Failure::Message)?;
+    let cross = matches!(args.mode, Mode::Cross);
+    let axis = |a: usize| &axes[if cross { a } else { 0 }];
+    let render = |item: Vec<usize>| -> String {
+        let parts: Vec<&str> = item
+            .iter()
+            .enumerate()
+            .map(|(a, l)| axis(a)[*l].as_str())
+            .collect();
+        parts.join(&args.separator)
+    };
+
+    if let Some(target) = &args.position_of {
+        let parts: Vec<&str> = match args.mode {
+            Mode::Shuffle => vec![target.as_str()],
+            _ => target.split(args.separator.as_str()).collect(),
+        };
+        // the first line with matching text stands for it, which is also what gets printed for it
+        let item: Option<Vec<usize>> = (!cross || parts.len() == axes.len())
+            .then(|| {
+                parts
+                    .iter()
+                    .enumerate()
+                    .map(|(a, p)| axis(a).iter().position(|l| l == p))
+                    .collect()
+            })
+            .flatten();
+        let p = item
+            .and_then(|item| space.index_of(&item))
+            .ok_or(Failure::NotFound)?;
+        writeln!(out, "{p}")?;
+        return Ok(());
+    }
+
+    match args.at {
+        Some(at) if at >= space.len() => {
+            return Err(Failure::Message(format!(
+                "position {at} is past the end of a space of {} items",
+                space.len()
+            )))
+        }
+        Some(at) => writeln!(out, "{}", render(space.get(at)))?,
+        None => {
+            for item in space.iter() {
+                writeln!(out, "{}", render(item))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return;
+        }
+        Err(e) => fail(e),
+    };
+    let files = if args.files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        args.files.clone()
+    };
+    let axes: Vec<Vec<String>> = match args.mode {
+        Mode::Cross => files.iter().map(|f| read_lines(f)).collect(),
+        _ => vec![files.iter().flat_map(|f| read_lines(f)).collect()],
+    };
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    match run(&args, &axes, &mut out).and_then(|_| Ok(out.flush()?)) {
+        Ok(()) => {}
+        Err(Failure::Message(m)) => fail(m),
+        Err(Failure::NotFound) => exit(1),
+        // being cut off by `head` is normal
+        Err(Failure::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(Failure::Io(e)) => fail(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(n: usize) -> Vec<String> {
+        (1..=n).map(|i| i.to_string()).collect()
+    }
+
+    /// runs the command line over the given axes, returning what it printed
+    fn output(command: &str, axes: &[Vec<String>]) -> Result<String, String> {
+        let args = parse_args(command.split_whitespace().map(String::from))?.unwrap();
+        let mut out = Vec::new();
+        match run(&args, axes, &mut out) {
+            Ok(()) => Ok(String::from_utf8(out).unwrap()),
+            Err(Failure::Message(m)) => Err(m),
+            Err(Failure::NotFound) => Err("not found".into()),
+            Err(Failure::Io(e)) => Err(e.to_string()),
+        }
+    }
+
+    #[test]
+    fn args() {
+        let a = parse_args(
+            ["subsets", "3", "--seed", "7", "--at", "5", "a.txt"]
+                .map(String::from)
+                .into_iter(),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(matches!(a.mode, Mode::Subsets(3)));
+        assert_eq!((a.config.seed, a.at), (7, Some(5)));
+        assert_eq!(a.files, ["a.txt"]);
+        assert!(parse_args(["--help"].map(String::from).into_iter())
+            .unwrap()
+            .is_none());
+        assert!(parse_args(["--shard", "3/3"].map(String::from).into_iter()).is_err());
+        assert!(parse_args(["--seed"].map(String::from).into_iter()).is_err());
+    }
+
+    #[test]
+    fn runs() {
+        let names = vec![lines(10)];
+        let all = output("--seed 3", &names).unwrap();
+        let mut sorted: Vec<&str> = all.lines().collect();
+        sorted.sort_by_key(|l| l.parse::<usize>().unwrap());
+        assert_eq!(sorted, lines(10));
+        let fourth = all.lines().nth(3).unwrap();
+        assert_eq!(output("--seed 3 --at 3", &names).unwrap().trim(), fourth);
+        assert_eq!(
+            output(&format!("--seed 3 --position-of {fourth}"), &names)
+                .unwrap()
+                .trim(),
+            "3"
+        );
+        assert!(output("--position-of nobody", &names).is_err());
+
+        let cross = output("cross --ordered --range 1..3", &[lines(2), lines(3)]).unwrap();
+        assert_eq!(cross, "1\t2\n1\t3\n");
+    }
+
+    #[test]
+    fn oversized_spaces() {
+        // too many to shuffle, but fine in order
+        assert!(output("permutations --at 0", &[lines(13)])
+            .unwrap_err()
+            .contains("--ordered"));
+        assert_eq!(
+            output("permutations --ordered --at 0", &[lines(13)])
+                .unwrap()
+                .trim(),
+            lines(13).join("\t")
+        );
+        assert!(output("subsets 3 --at 5", &[lines(100000)]).is_err());
+        // too many to count at all
+        assert!(output("permutations --ordered --at 0", &[lines(21)]).is_err());
+        assert!(output("subsets 21 --ordered --at 0", &[lines(40)]).is_err());
+        assert!(output("multisets 0", &[vec![]]).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Memoization for spaces with expensive `get`s, like an `into_map` with a closure that loads assets or runs a simulation.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use crate::Indexing;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheMode {
+    /// remembers every item that's ever been gotten. Allocates a slot per item of the space up front.
+    Full,
+    /// remembers the given number of most recently gotten items
+    Lru(usize),
+}
+
+enum Store<T> {
+    Full(Vec<Option<T>>),
+    Lru {
+        capacity: usize,
+        entries: HashMap<usize, (T, u64)>,
+        /// last use time → index, so the least recently used is the first entry
+        order: BTreeMap<u64, usize>,
+        tick: u64,
+    },
+}
+impl<T: Clone> Store<T> {
+    fn new(mode: CacheMode, len: usize) -> Self {
+        match mode {
+            CacheMode::Full => Store::Full((0..len).map(|_| None).collect()),
+            CacheMode::Lru(capacity) => Store::Lru {
+                capacity,
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+            },
+        }
+    }
+    fn lookup(&mut self, at: usize) -> Option<T> {
+        match self {
+            Store::Full(v) => v[at].clone(),
+            Store::Lru {
+                entries,
+                order,
+                tick,
+                ..
+            } => {
+                let (item, used) = entries.get_mut(&at)?;
+                order.remove(used);
+                *tick += 1;
+                *used = *tick;
+                order.insert(*tick, at);
+                Some(item.clone())
+            }
+        }
+    }
+    fn insert(&mut self, at: usize, item: T) {
+        match self {
+            Store::Full(v) => v[at] = Some(item),
+            Store::Lru {
+                capacity,
+                entries,
+                order,
+                tick,
+            } => {
+                if *capacity == 0 {
+                    return;
+                }
+                *tick += 1;
+                if let Some((_, used)) = entries.insert(at, (item, *tick)) {
+                    // another thread got here first
+                    order.remove(&used);
+                } else if entries.len() > *capacity {
+                    let (_, oldest) = order.pop_first().unwrap();
+                    entries.remove(&oldest);
+                }
+                order.insert(*tick, at);
+            }
+        }
+    }
+}
+
+/// Memoizes the items of a space. Not `Sync`, see `SyncCached` for that.
+pub struct Cached<I: Indexing> {
+    v: I,
+    store: RefCell<Store<I::Item>>,
+}
+impl<I> Cached<I>
+where
+    I: Indexing,
+    I::Item: Clone,
+{
+    pub fn new(v: I, mode: CacheMode) -> Self {
+        let store = RefCell::new(Store::new(mode, v.len()));
+        Self { v, store }
+    }
+    pub fn into_inner(self) -> I {
+        self.v
+    }
+}
+impl<I> Indexing for Cached<I>
+where
+    I: Indexing,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.v.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        if let Some(r) = self.store.borrow_mut().lookup(at) {
+            return r;
+        }
+        let r = self.v.get(at);
+        self.store.borrow_mut().insert(at, r.clone());
+        r
+    }
+}
+
+/// `Cached`, but shareable between threads. The lock isn't held while the inner space computes an item, so threads only contend on lookups, though two threads asking for the same missing item at once may both compute it.
+pub struct SyncCached<I: Indexing> {
+    v: I,
+    store: Mutex<Store<I::Item>>,
+}
+impl<I> SyncCached<I>
+where
+    I: Indexing,
+    I::Item: Clone,
+{
+    pub fn new(v: I, mode: CacheMode) -> Self {
+        let store = Mutex::new(Store::new(mode, v.len()));
+        Self { v, store }
+    }
+    pub fn into_inner(self) -> I {
+        self.v
+    }
+}
+impl<I> Indexing for SyncCached<I>
+where
+    I: Indexing,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.v.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        if let Some(r) = self.store.lock().unwrap().lookup(at) {
+            return r;
+        }
+        let r = self.v.get(at);
+        self.store.lock().unwrap().insert(at, r.clone());
+        r
+    }
+}
@@ -0,0 +1,107 @@
+//! Discretized continuous axes, for sweeping numeric parameters. Each is an ordinary space, so they go in `Cross`es and shuffles like any other.
+//! ```rust
+//! let sweep = light_shuffle(Cross(Logspace(1e-5, 1e-1, 9), Linspace(0.0, 0.9, 10)));
+//! // or keep refining until you run out of time, with every prefix spread over the whole interval
+//! for (dropout, seed) in infinite::FiniteCross(Linspace(0.0, 0.5, 6), DyadicRefinement(0.0, 1.0)).iter() {
+//!     train(dropout, seed);
+//! }
+//! ```
+
+use crate::{infinite::InfiniteIndexing, Indexing, InverseIndexing};
+
+/// n evenly spaced values from a to b, including both ends (just a, if n is 1)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Linspace<T>(pub T, pub T, pub usize);
+
+/// n geometrically spaced values from a to b, including both ends, so each is the same multiple of the last. a and b should be positive (or both negative).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Logspace<T>(pub T, pub T, pub usize);
+
+/// Every dyadic point between a and b, coarse to fine: a, b, then the midpoint, then the quarter points, then the eighths... Never repeats, and the first 2^k + 1 items are exactly the grid of spacing (b - a) / 2^k, so any prefix is spread over the whole interval. Floats run out of distinct midpoints after about 2^52 items, at which point it starts repeating.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DyadicRefinement<T>(pub T, pub T);
+
+macro_rules! float_spaces {
+    ($t:ty) => {
+        impl Indexing for Linspace<$t> {
+            type Item = $t;
+            fn len(&self) -> usize {
+                self.2
+            }
+            fn get(&self, at: usize) -> $t {
+                assert!(at < self.2);
+                if at == 0 {
+                    self.0
+                } else if at == self.2 - 1 {
+                    self.1
+                } else {
+                    let t = at as $t / (self.2 - 1) as $t;
+                    self.0 * (1.0 - t) + self.1 * t
+                }
+            }
+        }
+        /// finds the nearest index, then checks it gives exactly this value
+        impl InverseIndexing for Linspace<$t> {
+            fn index_of(&self, x: &$t) -> Option<usize> {
+                // when a == b every item is the same, and the division below would be 0 / 0
+                let nearest = if self.2 > 1 && self.0 != self.1 {
+                    ((x - self.0) / (self.1 - self.0) * (self.2 - 1) as $t).round()
+                } else {
+                    0.0
+                };
+                let i = nearest as usize;
+                (nearest >= 0.0 && i < self.2 && self.get(i) == *x).then_some(i)
+            }
+        }
+
+        impl Indexing for Logspace<$t> {
+            type Item = $t;
+            fn len(&self) -> usize {
+                self.2
+            }
+            fn get(&self, at: usize) -> $t {
+                assert!(at < self.2);
+                if at == 0 {
+                    self.0
+                } else if at == self.2 - 1 {
+                    self.1
+                } else {
+                    let t = at as $t / (self.2 - 1) as $t;
+                    self.0 * (self.1 / self.0).powf(t)
+                }
+            }
+        }
+        impl InverseIndexing for Logspace<$t> {
+            fn index_of(&self, x: &$t) -> Option<usize> {
+                // a == b would be 0 / 0 here too
+                let nearest = if self.2 > 1 && self.0 != self.1 {
+                    ((x / self.0).ln() / (self.1 / self.0).ln() * (self.2 - 1) as $t).round()
+                } else {
+                    0.0
+                };
+                let i = nearest as usize;
+                (nearest >= 0.0 && i < self.2 && self.get(i) == *x).then_some(i)
+            }
+        }
+
+        impl InfiniteIndexing for DyadicRefinement<$t> {
+            type Item = $t;
+            fn get(&self, at: u64) -> $t {
+                match at {
+                    0 => self.0,
+                    1 => self.1,
+                    _ => {
+                        // level k has the 2^k odd multiples of 1 / 2^(k + 1)
+                        let m = at - 1;
+                        let k = m.ilog2();
+                        let odd = 2 * (m - (1 << k)) + 1;
+                        let t = odd as $t / (2.0 as $t).powi(k as i32 + 1);
+                        self.0 * (1.0 - t) + self.1 * t
+                    }
+                }
+            }
+        }
+    };
+}
+float_spaces!(f32);
+float_spaces!(f64);
@@ -0,0 +1,165 @@
+//! Shuffled traversals of spaces that grow while they're being traversed, like an `IndexVec` that's being appended to or a `Range` whose end moves.
+//!
+//! Rebuilding a `Shuffled` for the new length would change the whole order, so items already visited could come up again and unvisited ones could be skipped. Instead, each growth starts a new epoch whose pool is whatever the previous epoch hadn't reached yet plus the new items, and only that pool gets shuffled.
+
+use std::ops::Range;
+
+use crate::{
+    persist::{Persist, PersistError, Reader, Writer},
+    rng::{self, DefaultShuffler, FromLength, Shuffler},
+    Indexing,
+};
+
+struct Epoch<S> {
+    /// the position in the overall order where this epoch takes over
+    start: usize,
+    /// how many unvisited items of the previous epoch carry over into this one. They're the first part of the pool.
+    carried: usize,
+    /// where in the previous epoch's order the carried items begin
+    carried_from: usize,
+    /// the items added to the space when this epoch began. The last part of the pool.
+    new: Range<usize>,
+    shuffler: S,
+}
+impl<S> Epoch<S> {
+    fn pool_len(&self) -> usize {
+        self.carried + self.new.end - self.new.start
+    }
+}
+
+/// A shuffled order over `0..len` that can be extended when `len` grows, without ever repeating an index or dropping one. `next_index` walks the order, and `grow_to` continues it from wherever the walk has got to. As an `Indexing`, position `at` of the order is at `get(at)`.
+pub struct GrowingShuffle<S = DefaultShuffler> {
+    epochs: Vec<Epoch<S>>,
+    len: usize,
+    at: usize,
+}
+impl<S> GrowingShuffle<S>
+where
+    S: Shuffler + FromLength,
+{
+    pub fn new(len: usize) -> Self {
+        Self {
+            epochs: vec![Epoch {
+                start: 0,
+                carried: 0,
+                carried_from: 0,
+                new: 0..len,
+                shuffler: S::for_length(len),
+            }],
+            len,
+            at: 0,
+        }
+    }
+    /// how much of the order has been walked by `next_index`
+    pub fn position(&self) -> usize {
+        self.at
+    }
+    /// the number of epochs, including the first. A new one starts each time the space grows after being partly walked.
+    pub fn epoch_count(&self) -> usize {
+        self.epochs.len()
+    }
+    pub fn next_index(&mut self) -> Option<usize> {
+        (self.at < self.len).then(|| {
+            self.at += 1;
+            self.get(self.at - 1)
+        })
+    }
+    /// adds `len..new_len` to the space. Positions before `position()` are left as they were, everything after is reshuffled to include the new items.
+    pub fn grow_to(&mut self, new_len: usize) {
+        assert!(new_len >= self.len, "spaces can only grow");
+        if new_len == self.len {
+            return;
+        }
+        let last = self.epochs.last_mut().unwrap();
+        if last.start == self.at {
+            // nothing of the current epoch has been visited, so it can just be widened
+            last.new = last.new.start..new_len;
+            last.shuffler = S::for_length(last.pool_len());
+        } else {
+            let consumed = self.at - last.start;
+            let carried = last.pool_len() - consumed;
+            let pool_len = carried + new_len - self.len;
+            self.epochs.push(Epoch {
+                start: self.at,
+                carried,
+                carried_from: consumed,
+                new: self.len..new_len,
+                shuffler: S::for_length(pool_len),
+            });
+        }
+        self.len = new_len;
+    }
+}
+impl<S> Indexing for GrowingShuffle<S>
+where
+    S: Shuffler,
+{
+    type Item = usize;
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn get(&self, at: usize) -> usize {
+        assert!(at < self.len);
+        let mut k = self.epochs.partition_point(|e| e.start <= at) - 1;
+        let mut i = at - self.epochs[k].start;
+        loop {
+            let e = &self.epochs[k];
+            let j = rng::permute(&e.shuffler, e.pool_len() as u64, i as u64) as usize;
+            if j < e.carried {
+                i = e.carried_from + j;
+                k -= 1;
+            } else {
+                return e.new.start + j - e.carried;
+            }
+        }
+    }
+}
+
+impl<S: Persist + Shuffler> Persist for GrowingShuffle<S> {
+    const TAG: &'static str = "GrowingShuffle";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.len);
+        w.value(&self.at);
+        w.value(&self.epochs.len());
+        for e in self.epochs.iter() {
+            w.value(&e.start);
+            w.value(&e.carried);
+            w.value(&e.carried_from);
+            w.value(&e.new);
+            w.value(&e.shuffler);
+        }
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let len: usize = r.value()?;
+        let at: usize = r.value()?;
+        let count: usize = r.value()?;
+        let mut epochs: Vec<Epoch<S>> = Vec::new();
+        for _ in 0..count {
+            let e: Epoch<S> = Epoch {
+                start: r.value()?,
+                carried: r.value()?,
+                carried_from: r.value()?,
+                new: r.value()?,
+                shuffler: r.value()?,
+            };
+            let fits = e.new.start <= e.new.end
+                && match epochs.last() {
+                    None => e.start == 0 && e.carried == 0 && e.new.start == 0,
+                    Some(p) => {
+                        e.start > p.start
+                            && e.carried_from == e.start - p.start
+                            && e.carried + e.carried_from == p.pool_len()
+                            && e.new.start == p.new.end
+                    }
+                };
+            if !fits || e.shuffler.period() < e.pool_len() as u64 {
+                return Err(PersistError::Malformed("inconsistent epochs".into()));
+            }
+            epochs.push(e);
+        }
+        match epochs.last() {
+            Some(l) if l.new.end == len && at <= len => Ok(GrowingShuffle { epochs, len, at }),
+            _ => Err(PersistError::Malformed("inconsistent epochs".into())),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Spaces without an end. `InfiniteIndexing` is `Indexing` with no `len`, indexed by `u64` so that it doesn't run out on 32 bit platforms.
+//! ```rust
+//! // unbounded procgen seeds, in a shuffled order, never repeating
+//! for (seed, variant) in infinite::Shuffled::new(infinite::FiniteCross(0..4, Naturals)).iter() {
+//!     generate(seed, variant);
+//! }
+//! ```
+
+use std::iter::FusedIterator;
+
+use crate::{
+    rng::{self, ShufflerConfig},
+    Indexing,
+};
+
+pub trait InfiniteIndexing {
+    type Item;
+    fn get(&self, at: u64) -> Self::Item;
+    fn iter(&self) -> InfiniteIter<'_, Self> {
+        InfiniteIter { v: self, at: 0 }
+    }
+}
+
+impl<I> InfiniteIndexing for &I
+where
+    I: InfiniteIndexing + ?Sized,
+{
+    type Item = I::Item;
+    fn get(&self, at: u64) -> Self::Item {
+        (**self).get(at)
+    }
+}
+
+pub struct InfiniteIter<'a, I: ?Sized> {
+    pub v: &'a I,
+    pub at: u64,
+}
+impl<I> Iterator for InfiniteIter<'_, I>
+where
+    I: InfiniteIndexing + ?Sized,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        let r = self.v.get(self.at);
+        self.at += 1;
+        Some(r)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.at += n as u64;
+        self.next()
+    }
+}
+impl<I> FusedIterator for InfiniteIter<'_, I> where I: InfiniteIndexing + ?Sized {}
+
+/// 0, 1, 2...
+#[derive(Clone, Copy)]
+pub struct Naturals;
+impl InfiniteIndexing for Naturals {
+    type Item = u64;
+    fn get(&self, at: u64) -> u64 {
+        at
+    }
+}
+
+/// Szudzik's pairing function. Every prefix of length n² covers exactly the square n × n.
+pub fn unpair(z: u64) -> (u64, u64) {
+    let s = z.isqrt();
+    let r = z - s * s;
+    if r < s {
+        (r, s)
+    } else {
+        (s, r - s)
+    }
+}
+pub fn pair(x: u64, y: u64) -> u64 {
+    if x < y {
+        y * y + x
+    } else {
+        x * x + x + y
+    }
+}
+
+/// Every pairing of an item of A with an item of B, where both are infinite. Goes out in squares (see `unpair`), so each axis gets explored at the same rate.
+#[derive(Clone)]
+pub struct Cross<A, B>(pub A, pub B);
+impl<A, B> InfiniteIndexing for Cross<A, B>
+where
+    A: InfiniteIndexing,
+    B: InfiniteIndexing,
+{
+    type Item = (A::Item, B::Item);
+    fn get(&self, at: u64) -> Self::Item {
+        let (x, y) = unpair(at);
+        (self.0.get(x), self.1.get(y))
+    }
+}
+
+/// Every pairing of an item of finite A with an item of infinite B. Goes through all of A for each item of B. A mustn't be empty, since then there'd be no items at all.
+#[derive(Clone)]
+pub struct FiniteCross<A, B>(pub A, pub B);
+impl<A, B> InfiniteIndexing for FiniteCross<A, B>
+where
+    A: Indexing,
+    B: InfiniteIndexing,
+{
+    type Item = (A::Item, B::Item);
+    fn get(&self, at: u64) -> Self::Item {
+        let l = self.0.len() as u64;
+        assert!(l > 0, "FiniteCross with an empty finite side has no items");
+        (self.0.get((at % l) as usize), self.1.get(at / l))
+    }
+}
+
+/// The first n items of an infinite space, as a finite one
+#[derive(Clone)]
+pub struct Prefix<I>(pub usize, pub I);
+impl<I> Indexing for Prefix<I>
+where
+    I: InfiniteIndexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.0
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.0);
+        self.1.get(at as u64)
+    }
+}
+
+/// lfsrs only go up to 32 bits, so blocks stop doubling here
+const MAX_BLOCK_BITS: u32 = 30;
+
+/// the block containing `at`, as (start, length). Blocks are `[0, 1)`, then `[2^k, 2^(k+1))` until they reach `2^MAX_BLOCK_BITS` long, after which they stay that long.
+fn block(at: u64) -> (u64, u64) {
+    let cap = 1 << MAX_BLOCK_BITS;
+    if at == 0 {
+        (0, 1)
+    } else if at < 2 * cap {
+        let k = at.ilog2();
+        (1 << k, 1 << k)
+    } else {
+        (at - (at - 2 * cap) % cap, cap)
+    }
+}
+
+/// Visits every item of an infinite space exactly once, in a shuffled order. The space is cut into blocks that double in length (up to a cap of 2^30) and each block is shuffled on its own, so the nth item out always comes from within about 2n of the start of the space. Each block's shuffle is seeded from the config's seed and where the block starts, so blocks of the same length don't repeat each other's order.
+pub struct Shuffled<I> {
+    v: I,
+    config: ShufflerConfig,
+}
+impl<I> Shuffled<I>
+where
+    I: InfiniteIndexing,
+{
+    pub fn new(v: I) -> Self {
+        Self::with_config(v, &ShufflerConfig::default())
+    }
+    /// shuffles each block with the algorithm and rounds given in `config`, seeded by its seed
+    pub fn with_config(v: I, config: &ShufflerConfig) -> Self {
+        Self { v, config: *config }
+    }
+    /// the index into the underlying space that the `at`th item comes from
+    pub fn source_index(&self, at: u64) -> u64 {
+        let (start, len) = block(at);
+        let config = ShufflerConfig {
+            seed: rng::splitmix64(self.config.seed ^ rng::splitmix64(start)),
+            ..self.config
+        };
+        start + rng::permute(&config.for_length(len as usize), len, at - start)
+    }
+}
+impl<I> InfiniteIndexing for Shuffled<I>
+where
+    I: InfiniteIndexing,
+{
+    type Item = I::Item;
+    fn get(&self, at: u64) -> Self::Item {
+        self.v.get(self.source_index(at))
+    }
+}
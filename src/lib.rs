@@ -1,19 +1,38 @@
-#![feature(isqrt)]
-
-use std::{borrow::Borrow, hash::Hash, marker::PhantomData, ops::Range};
+use std::{
+    borrow::Borrow, collections::HashSet, hash::Hash, iter::FusedIterator, marker::PhantomData,
+    ops::Range,
+};
 
+pub mod cache;
+pub mod continuous;
+pub mod growing;
+pub mod infinite;
+pub mod par;
+pub mod persist;
+pub mod progress;
 pub mod rng;
-use rng::{Shuffler, DefaultShuffler};
+pub mod sample;
+pub mod weighted;
+use rng::{DefaultShuffler, DynShuffler, FromLength, Seeded, Shuffler, ShufflerConfig};
 
 /// if you like shuffling combinatorial objects, you may also like this combinatorial object library, I sure do
 pub use number_encoding;
 
+/// items that can be deduplicated, see `Distinct`, `Union` and `UnionVec`
 pub trait Memorable: Hash + Eq {}
+impl<T: Hash + Eq> Memorable for T {}
 
 pub trait Indexing {
     type Item;
     fn len(&self) -> usize;
     fn get(&self, at: usize) -> Self::Item;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// gets every item up front, see also `cache::Cached`
+    fn materialize(&self) -> IndexVec<Self::Item> {
+        IndexVec((0..self.len()).map(|i| self.get(i)).collect())
+    }
     fn into_iter(self) -> IndexingIter<Self, Self>
     where
         Self: Sized,
@@ -82,13 +101,14 @@ where
 }
 
 #[derive(Clone)]
+/// Iterates any `Indexing` through `get`. Since that's random access, skipping, stepping, counting and reversing are all constant time. `at` is the next index from the front and `len` is one past the next index from the back. For spaces that implement `Successor`, `OpsRef::iter_successive` steps from each item to the next instead, which is faster in order. This can't do that itself: it covers every `Indexing`, and picking a different `next` for just the ones that are also `Successor` would take specialization, which stable Rust doesn't have.
 pub struct IndexingIter<D, I: ?Sized> {
     pub v: D,
     pub at: usize,
     pub len: usize,
     pub _i: PhantomData<I>,
 }
-impl<'a, DI, I> Iterator for IndexingIter<DI, I>
+impl<DI, I> Iterator for IndexingIter<DI, I>
 where
     DI: Borrow<I>,
     I: ?Sized + Indexing,
@@ -103,6 +123,51 @@ where
             r
         }
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let l = self.len.saturating_sub(self.at);
+        (l, Some(l))
+    }
+    fn count(self) -> usize {
+        self.len.saturating_sub(self.at)
+    }
+    /// random access, so skipping is free
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.at = self.at.saturating_add(n).min(self.len);
+        self.next()
+    }
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+impl<DI, I> DoubleEndedIterator for IndexingIter<DI, I>
+where
+    DI: Borrow<I>,
+    I: ?Sized + Indexing,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.at >= self.len {
+            None
+        } else {
+            self.len -= 1;
+            Some(self.v.borrow().get(self.len))
+        }
+    }
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.len = self.len.saturating_sub(n).max(self.at);
+        self.next_back()
+    }
+}
+impl<DI, I> ExactSizeIterator for IndexingIter<DI, I>
+where
+    DI: Borrow<I>,
+    I: ?Sized + Indexing,
+{
+}
+impl<DI, I> FusedIterator for IndexingIter<DI, I>
+where
+    DI: Borrow<I>,
+    I: ?Sized + Indexing,
+{
 }
 /// I straight up don't know how to abstract over different kinds of references dynamic or not. It may not be possible. I'll just make everything public so that you can do what you need to.
 pub fn dyn_iter<I: Indexing + ?Sized>(v: Box<I>) -> IndexingIter<Box<I>, I> {
@@ -117,17 +182,27 @@ pub fn dyn_iter<I: Indexing + ?Sized>(v: Box<I>) -> IndexingIter<Box<I>, I> {
 
 /// separated from the above because these are not object-safe
 pub trait OpsRef {
-    fn iter<'a>(&'a self) -> IndexingIter<&'a Self, Self>;
-    fn map<'a, F, R>(&'a self, f: F) -> IndexingMap<&'a Self, Self, F>
+    fn iter(&self) -> IndexingIter<&Self, Self>;
+    /// like `iter`, but stepping from item to item, see `Successive`
+    fn iter_successive(&self) -> Successive<&Self, Self>
+    where
+        Self: Successor;
+    fn map<F, R>(&self, f: F) -> IndexingMap<&Self, Self, F>
     where
         Self: Indexing,
         F: Fn(Self::Item) -> R;
+    fn window(&self, range: Range<usize>) -> Window<&Self>;
+    fn truncated(&self, n: usize) -> Truncate<&Self>;
+    fn skipped(&self, n: usize) -> Skip<&Self>;
+    fn stepped_by(&self, k: usize) -> StepBy<&Self>;
+    fn reversed(&self) -> Rev<&Self>;
+    fn repeated(&self, times: usize) -> Repeat<&Self>;
 }
 impl<I> OpsRef for I
 where
     I: Indexing + ?Sized,
 {
-    fn iter<'a>(&'a self) -> IndexingIter<&'a Self, Self> {
+    fn iter(&self) -> IndexingIter<&Self, Self> {
         let len = self.len();
         IndexingIter {
             v: self,
@@ -136,7 +211,13 @@ where
             _i: PhantomData,
         }
     }
-    fn map<'a, F, R>(&'a self, f: F) -> IndexingMap<&'a Self, Self, F>
+    fn iter_successive(&self) -> Successive<&Self, Self>
+    where
+        Self: Successor,
+    {
+        Successive::new(self)
+    }
+    fn map<F, R>(&self, f: F) -> IndexingMap<&Self, Self, F>
     where
         Self: Indexing,
     {
@@ -146,6 +227,24 @@ where
             _i: PhantomData,
         }
     }
+    fn window(&self, range: Range<usize>) -> Window<&Self> {
+        Window(range, self)
+    }
+    fn truncated(&self, n: usize) -> Truncate<&Self> {
+        Truncate(n, self)
+    }
+    fn skipped(&self, n: usize) -> Skip<&Self> {
+        Skip(n, self)
+    }
+    fn stepped_by(&self, k: usize) -> StepBy<&Self> {
+        StepBy(k, self)
+    }
+    fn reversed(&self) -> Rev<&Self> {
+        Rev(self)
+    }
+    fn repeated(&self, times: usize) -> Repeat<&Self> {
+        Repeat(times, self)
+    }
 }
 
 // /// this has to be a function because trait objects can't return self types
@@ -154,6 +253,170 @@ where
 //     IndexingIter { v, at: 0, len }
 // }
 
+/// Spaces whose items own allocations, like `KSubsets`' `Vec`s, that can write an item into a buffer the caller keeps reusing instead, so tight loops don't touch the allocator. See also the const generic `KSubsetsN`, whose items are arrays.
+/// ```rust
+/// let space = light_shuffle(KSubsets::new(60, 4));
+/// let mut buf = [0; 4];
+/// for i in 0..space.len() {
+///     space.get_into(i, &mut buf);
+///     score(&buf);
+/// }
+/// ```
+pub trait IndexingInto: Indexing {
+    type Buf: ?Sized;
+    /// writes item `at` into `out`, which must be the right size for it
+    fn get_into(&self, at: usize, out: &mut Self::Buf);
+}
+impl<I> IndexingInto for &I
+where
+    I: IndexingInto + ?Sized,
+{
+    type Buf = I::Buf;
+    fn get_into(&self, at: usize, out: &mut Self::Buf) {
+        (**self).get_into(at, out)
+    }
+}
+
+/// Spaces that can step an item to the one after it more cheaply than `get`ting that one, like the next combination of `KSubsets`, for iterating in order with `Successive`. `advance` must agree with `get`.
+/// ```rust
+/// for (pair, seed) in Cross(KSubsets::new(40, 2), 0..1000).iter_successive() {
+///     run(pair, seed);
+/// }
+/// ```
+pub trait Successor: Indexing {
+    /// turns item `at` into item `at + 1`, which must exist
+    fn advance(&self, at: usize, item: &mut Self::Item);
+}
+impl<I> Successor for &I
+where
+    I: Successor + ?Sized,
+{
+    fn advance(&self, at: usize, item: &mut Self::Item) {
+        (**self).advance(at, item)
+    }
+}
+
+/// Iterates a `Successor` space, stepping each item from the last one it gave out instead of `get`ting it, which it can do as long as it's going forwards one at a time. Skips and the back end still use `get`, so those stay constant time, and nothing is stepped until it's asked for. Keeps a clone of the last item.
+pub struct Successive<D, I: ?Sized + Indexing> {
+    v: D,
+    at: usize,
+    len: usize,
+    /// the last item from the front, and its index
+    last: Option<(usize, I::Item)>,
+    _i: PhantomData<I>,
+}
+impl<D, I> Successive<D, I>
+where
+    D: Borrow<I>,
+    I: ?Sized + Successor,
+{
+    pub fn new(v: D) -> Self {
+        let len = v.borrow().len();
+        Self {
+            v,
+            at: 0,
+            len,
+            last: None,
+            _i: PhantomData,
+        }
+    }
+}
+impl<D, I> Clone for Successive<D, I>
+where
+    D: Clone,
+    I: ?Sized + Indexing,
+    I::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            v: self.v.clone(),
+            at: self.at,
+            len: self.len,
+            last: self.last.clone(),
+            _i: PhantomData,
+        }
+    }
+}
+impl<D, I> Iterator for Successive<D, I>
+where
+    D: Borrow<I>,
+    I: ?Sized + Successor,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.at >= self.len {
+            return None;
+        }
+        let v = self.v.borrow();
+        let r = match self.last.take() {
+            Some((i, mut item)) if i + 1 == self.at => {
+                v.advance(i, &mut item);
+                item
+            }
+            _ => v.get(self.at),
+        };
+        if self.at + 1 < self.len {
+            self.last = Some((self.at, r.clone()));
+        }
+        self.at += 1;
+        Some(r)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let l = self.len.saturating_sub(self.at);
+        (l, Some(l))
+    }
+    fn count(self) -> usize {
+        self.len.saturating_sub(self.at)
+    }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.at = self.at.saturating_add(n).min(self.len);
+        self.next()
+    }
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+impl<D, I> DoubleEndedIterator for Successive<D, I>
+where
+    D: Borrow<I>,
+    I: ?Sized + Successor,
+    I::Item: Clone,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.at >= self.len {
+            None
+        } else {
+            self.len -= 1;
+            Some(self.v.borrow().get(self.len))
+        }
+    }
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.len = self.len.saturating_sub(n).max(self.at);
+        self.next_back()
+    }
+}
+impl<D, I> ExactSizeIterator for Successive<D, I>
+where
+    D: Borrow<I>,
+    I: ?Sized + Successor,
+    I::Item: Clone,
+{
+}
+impl<D, I> FusedIterator for Successive<D, I>
+where
+    D: Borrow<I>,
+    I: ?Sized + Successor,
+    I::Item: Clone,
+{
+}
+
+/// Spaces that can find where an item is, the inverse of `get`
+pub trait InverseIndexing: Indexing {
+    /// `None` if the item isn't in the space. Where an item occurs more than once, any of its indices may be returned.
+    fn index_of(&self, item: &Self::Item) -> Option<usize>;
+}
+
 #[derive(Clone)]
 pub struct Once<T>(pub T);
 impl<T> Indexing for Once<T>
@@ -170,6 +433,14 @@ where
         self.0.clone()
     }
 }
+impl<T> InverseIndexing for Once<T>
+where
+    T: Clone + PartialEq,
+{
+    fn index_of(&self, item: &T) -> Option<usize> {
+        (*item == self.0).then_some(0)
+    }
+}
 
 /// Yeilds the pairing of each element in A with every element in B
 #[derive(Hash, PartialEq, Eq, Clone)]
@@ -190,6 +461,83 @@ where
         (self.0.get(q), self.1.get(r))
     }
 }
+impl<A, B, TA, TB> InverseIndexing for Cross<A, B>
+where
+    A: InverseIndexing<Item = TA>,
+    B: InverseIndexing<Item = TB>,
+{
+    fn index_of(&self, (a, b): &(TA, TB)) -> Option<usize> {
+        Some(self.0.index_of(a)? * self.1.len() + self.1.index_of(b)?)
+    }
+}
+/// an odometer: steps B, and when B wraps around, steps A and starts B over
+impl<A, B> Successor for Cross<A, B>
+where
+    A: Successor,
+    B: Successor,
+{
+    fn advance(&self, at: usize, (a, b): &mut Self::Item) {
+        let ato = self.1.len();
+        let r = at % ato;
+        if r + 1 < ato {
+            self.1.advance(r, b);
+        } else {
+            self.0.advance(at / ato, a);
+            *b = self.1.get(0);
+        }
+    }
+}
+
+/// so that combinators can take a space by reference and several of them can share one
+impl<I> Indexing for &I
+where
+    I: Indexing + ?Sized,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        (**self).get(at)
+    }
+}
+impl<I> InverseIndexing for &I
+where
+    I: InverseIndexing + ?Sized,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        (**self).index_of(item)
+    }
+}
+/// so that a space chosen at runtime, a `Box<dyn Indexing<Item = ...>>`, can go into combinators
+impl<I> Indexing for Box<I>
+where
+    I: Indexing + ?Sized,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        (**self).get(at)
+    }
+}
+impl<I> InverseIndexing for Box<I>
+where
+    I: InverseIndexing + ?Sized,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        (**self).index_of(item)
+    }
+}
+impl<I> Successor for Box<I>
+where
+    I: Successor + ?Sized,
+{
+    fn advance(&self, at: usize, item: &mut Self::Item) {
+        (**self).advance(at, item)
+    }
+}
 
 impl Indexing for Range<usize> {
     type Item = usize;
@@ -200,6 +548,16 @@ impl Indexing for Range<usize> {
         self.start + at
     }
 }
+impl InverseIndexing for Range<usize> {
+    fn index_of(&self, item: &usize) -> Option<usize> {
+        self.contains(item).then(|| item - self.start)
+    }
+}
+impl Successor for Range<usize> {
+    fn advance(&self, _at: usize, item: &mut usize) {
+        *item += 1;
+    }
+}
 
 /// does all of A, then does B
 #[derive(Clone)]
@@ -218,131 +576,1748 @@ where
         if at < ot {
             Ok(self.0.get(at))
         } else {
-            Err(self.1.get(at))
+            Err(self.1.get(at - ot))
         }
     }
 }
-
-/// Iterates k-sized subsets of the n-sized input set
-#[derive(Clone)]
-pub struct KSubsets {
-    n: usize,
-    k: usize,
-}
-impl KSubsets {
-    pub fn new(n: usize, k: usize) -> Self {
-        Self { n, k }
+impl<A, B, TA, TB> InverseIndexing for Series<A, B>
+where
+    A: InverseIndexing<Item = TA>,
+    B: InverseIndexing<Item = TB>,
+{
+    fn index_of(&self, item: &Result<TA, TB>) -> Option<usize> {
+        match item {
+            Ok(a) => self.0.index_of(a),
+            Err(b) => Some(self.0.len() + self.1.index_of(b)?),
+        }
     }
 }
-impl Indexing for KSubsets {
-    type Item = Vec<usize>;
+
+/// alternates between A and B, then does the rest of whichever is longer
+#[derive(Clone)]
+pub struct Interleave<A, B>(pub A, pub B);
+impl<A, B> Indexing for Interleave<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    type Item = Result<A::Item, B::Item>;
     fn len(&self) -> usize {
-        number_encoding::combination(self.n, self.k)
+        self.0.len() + self.1.len()
     }
     fn get(&self, at: usize) -> Self::Item {
-        number_encoding::combinadics::decode(at, self.k)
+        let m = self.0.len().min(self.1.len());
+        if at < 2 * m {
+            if at.is_multiple_of(2) {
+                Ok(self.0.get(at / 2))
+            } else {
+                Err(self.1.get(at / 2))
+            }
+        } else if self.0.len() > m {
+            Ok(self.0.get(at - m))
+        } else {
+            Err(self.1.get(at - m))
+        }
+    }
+}
+impl<A, B> InverseIndexing for Interleave<A, B>
+where
+    A: InverseIndexing,
+    B: InverseIndexing,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        let m = self.0.len().min(self.1.len());
+        let (i, side) = match item {
+            Ok(a) => (self.0.index_of(a)?, 0),
+            Err(b) => (self.1.index_of(b)?, 1),
+        };
+        Some(if i < m { 2 * i + side } else { i + m })
     }
 }
 
-/// Iterates k-sized multiset (where entries are allowed to repeat) subsets of the n-sized input set
+/// Round robin over any number of spaces, tagging each item with the index of the space it came from. Spaces drop out of the rotation as they run out.
 #[derive(Clone)]
-pub struct KSubmultisets {
-    n: usize,
-    k: usize,
+pub struct InterleaveVec<I> {
+    spaces: Vec<I>,
+    /// the rotation changes each time a space runs out, so the output is a series of phases. Each is (round it starts at, position it starts at, spaces in the rotation).
+    phases: Vec<(usize, usize, Vec<usize>)>,
+    len: usize,
 }
-impl KSubmultisets {
-    pub fn new(n: usize, k: usize) -> Self {
-        Self { n, k }
+impl<I> InterleaveVec<I>
+where
+    I: Indexing,
+{
+    pub fn new(spaces: Vec<I>) -> Self {
+        let lens: Vec<usize> = spaces.iter().map(|s| s.len()).collect();
+        let mut ends = lens.clone();
+        ends.sort_unstable();
+        ends.dedup();
+        let mut phases = Vec::new();
+        let (mut round, mut start) = (0, 0);
+        for end in ends {
+            if end == 0 {
+                continue;
+            }
+            let active: Vec<usize> = (0..lens.len()).filter(|s| lens[*s] > round).collect();
+            let next = start + (end - round) * active.len();
+            phases.push((round, start, active));
+            round = end;
+            start = next;
+        }
+        Self {
+            spaces,
+            phases,
+            len: start,
+        }
     }
 }
-impl Indexing for KSubmultisets {
-    type Item = Vec<usize>;
+impl<I> Indexing for InterleaveVec<I>
+where
+    I: Indexing,
+{
+    type Item = (usize, I::Item);
     fn len(&self) -> usize {
-        number_encoding::combination(self.n + self.k - 1, self.k)
+        self.len
     }
     fn get(&self, at: usize) -> Self::Item {
-        let mut r = number_encoding::combinadics::decode(at, self.k);
-        for (i, v) in r.iter_mut().enumerate() {
-            *v -= i
-        }
-        r
+        assert!(at < self.len);
+        let p = self.phases.partition_point(|ph| ph.1 <= at) - 1;
+        let (round, start, active) = &self.phases[p];
+        let offset = at - start;
+        let s = active[offset % active.len()];
+        (s, self.spaces[s].get(round + offset / active.len()))
     }
 }
-
-#[derive(Clone)]
-pub struct IndexVec<T> (pub Vec<T>);
-impl<T> Indexing for IndexVec<T> where T:Clone {
-    type Item = T;
-    fn len(&self) -> usize {
-        self.0.len()
-    }
-    fn get(&self, at: usize) -> Self::Item {
-        self.0[at].clone()
+impl<I> InverseIndexing for InterleaveVec<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, (s, item): &Self::Item) -> Option<usize> {
+        let i = self.spaces.get(*s)?.index_of(item)?;
+        let p = self.phases.partition_point(|ph| ph.0 <= i) - 1;
+        let (round, start, active) = &self.phases[p];
+        let k = active.binary_search(s).ok()?;
+        Some(start + (i - round) * active.len() + k)
     }
 }
 
-pub struct Truncate<I>(pub usize, pub I);
-impl<I> Indexing for Truncate<I> where I:Indexing {
-    type Item=I::Item;
+/// `partition_point` over `0..n` without a slice: the first index where `pred` stops holding, for a `pred` that's true then false
+pub(crate) fn partition_point_to(n: usize, pred: impl Fn(usize) -> bool) -> usize {
+    let (mut lo, mut hi) = (0, n);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
 
-    fn len(&self) -> usize {
-        self.0.min(self.1.len())
+/// how many of the `lt` keys of a space, `(2i + 1) / 2lt`, come before the key `(2j + 1) / 2ls` of another. `inclusive` counts equal keys too.
+fn keys_before(lt: usize, j: usize, ls: usize, inclusive: bool) -> usize {
+    // counts the odd numbers o with o / 2lt < (2j + 1) / 2ls, ie, o·ls < (2j + 1)·lt
+    if lt == 0 {
+        return 0;
     }
+    let q = (2 * j as u128 + 1) * lt as u128;
+    let below = if inclusive {
+        q / ls as u128
+    } else {
+        (q - 1) / ls as u128
+    };
+    (below.div_ceil(2) as usize).min(lt)
+}
 
-    fn get(&self, at: usize) -> Self::Item {
-        assert!(at < self.len());
-        self.1.get(at)
+/// where item `j` of space `s` goes when spaces of the given lengths are spread proportionally, see `ProportionalInterleave`
+pub(crate) fn proportional_rank(lens: &[usize], s: usize, j: usize) -> usize {
+    lens.iter()
+        .enumerate()
+        .map(|(t, lt)| keys_before(*lt, j, lens[s], t < s))
+        .sum()
+}
+
+/// the inverse of `proportional_rank`, as (space, item within it), where `total` is the sum of `lens`. An item's rank is within n of its key times the total, so only the few items per space whose keys are near `at / total` can be it: those are gathered, put in order, and binary searched by rank. O(n log n) for n spaces, whatever their lengths.
+pub(crate) fn proportional_unrank(lens: &[usize], total: usize, at: usize) -> (usize, usize) {
+    assert!(at < total, "position {at} is past the end");
+    let margin = lens.len() as u128 + 1;
+    let (lo, hi) = ((at as u128).saturating_sub(margin), at as u128 + margin);
+    let mut near: Vec<(usize, usize)> = Vec::new();
+    for (s, l) in lens.iter().enumerate() {
+        if *l == 0 {
+            continue;
+        }
+        let first = (lo * *l as u128 / total as u128) as usize;
+        let last = ((hi * *l as u128).div_ceil(total as u128) as usize).min(l - 1);
+        near.extend((first..=last).map(|j| (s, j)));
     }
+    // by key, (2j + 1) / 2l, then by space
+    near.sort_unstable_by(|(s, j), (t, i)| {
+        ((2 * *j as u128 + 1) * lens[*t] as u128)
+            .cmp(&((2 * *i as u128 + 1) * lens[*s] as u128))
+            .then(s.cmp(t))
+    });
+    let k = near.partition_point(|(s, j)| proportional_rank(lens, *s, *j) < at);
+    near[k]
 }
 
-/// psuedorandomly permutes the given Indexing
+/// Spreads the items of each space evenly across the whole output, so every prefix has about as many of each space as its share of the total length. Item `j` of a space of length `l` goes at about `(j + 0.5) / l` of the way through, ties going to the earlier space. Items are tagged with the index of the space they came from. `get` is O(n log n) for n spaces.
 /// ```rust
-/// Shuffled::<_, rng::DefaultShuffler>::new(Cross(0..3, 0..2))
+/// // 1 curated case for every 3 generated ones, all the way through
+/// let cases = ProportionalInterleave::new(vec![curated, generated]);
 /// ```
 #[derive(Clone)]
-pub struct Shuffled<D, S> {
-    v: D,
-    r: S,
+pub struct ProportionalInterleave<I> {
+    spaces: Vec<I>,
+    lens: Vec<usize>,
+    len: usize,
 }
-impl<D, S> Shuffled<D, S> {
-    pub fn new(v: D) -> Shuffled<D, S>
-    where
-        D: Indexing,
-        S: Shuffler,
-    {
-        let length = v.len();
-        Self {
-            v,
-            r: S::for_length(length),
-        }
+impl<I> ProportionalInterleave<I>
+where
+    I: Indexing,
+{
+    pub fn new(spaces: Vec<I>) -> Self {
+        let lens: Vec<usize> = spaces.iter().map(|s| s.len()).collect();
+        let len = lens.iter().sum();
+        Self { spaces, lens, len }
     }
 }
-impl<D, S> Indexing for Shuffled<D, S>
+impl<I> Indexing for ProportionalInterleave<I>
 where
-    D: Indexing,
+    I: Indexing,
+{
+    type Item = (usize, I::Item);
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len);
+        let (s, j) = proportional_unrank(&self.lens, self.len, at);
+        (s, self.spaces[s].get(j))
+    }
+}
+impl<I> InverseIndexing for ProportionalInterleave<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, (s, item): &Self::Item) -> Option<usize> {
+        let j = self.spaces.get(*s)?.index_of(item)?;
+        Some(proportional_rank(&self.lens, *s, j))
+    }
+}
+
+/// The items of a space with repeats removed, keeping the first occurrence of each. For spaces whose `get` maps many indices to the same item, eg, after `into_map`. Construction makes one pass over the space, hashing every item, and keeps an index table, so `len` is exact and `get` is still random access.
+#[derive(Clone)]
+pub struct Distinct<I> {
+    v: I,
+    table: Vec<usize>,
+}
+impl<I> Distinct<I>
+where
+    I: Indexing,
+    I::Item: Memorable,
+{
+    pub fn new(v: I) -> Self {
+        let mut seen = HashSet::new();
+        let table = (0..v.len()).filter(|i| seen.insert(v.get(*i))).collect();
+        Self { v, table }
+    }
+}
+impl<I> Indexing for Distinct<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.table.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        self.v.get(self.table[at])
+    }
+}
+
+/// All of the items in A, then the items of B that weren't in A, with repeats removed. Built with a hashing pass like `Distinct`. For more than two spaces, use `UnionVec`.
+#[derive(Clone)]
+pub struct Union<A, B> {
+    a: A,
+    b: B,
+    table: Vec<Result<usize, usize>>,
+}
+impl<A, B, T> Union<A, B>
+where
+    A: Indexing<Item = T>,
+    B: Indexing<Item = T>,
+    T: Memorable,
+{
+    pub fn new(a: A, b: B) -> Self {
+        let mut seen = HashSet::new();
+        let mut table: Vec<_> = (0..a.len())
+            .filter(|i| seen.insert(a.get(*i)))
+            .map(Ok)
+            .collect();
+        table.extend((0..b.len()).filter(|i| seen.insert(b.get(*i))).map(Err));
+        Self { a, b, table }
+    }
+}
+impl<A, B, T> Indexing for Union<A, B>
+where
+    A: Indexing<Item = T>,
+    B: Indexing<Item = T>,
+{
+    type Item = T;
+    fn len(&self) -> usize {
+        self.table.len()
+    }
+    fn get(&self, at: usize) -> T {
+        match self.table[at] {
+            Ok(i) => self.a.get(i),
+            Err(i) => self.b.get(i),
+        }
+    }
+}
+
+/// `Union` of any number of spaces: the items of each in turn, skipping any that an earlier one already gave. One hashing pass over all of them, rather than one per pair as with nested `Union`s.
+/// ```rust
+/// let configs = UnionVec::new(vec![baseline, from_last_week, suggested]);
+/// ```
+#[derive(Clone)]
+pub struct UnionVec<I> {
+    spaces: Vec<I>,
+    /// (space, index within it) of each item
+    table: Vec<(usize, usize)>,
+}
+impl<I> UnionVec<I>
+where
+    I: Indexing,
+    I::Item: Memorable,
+{
+    pub fn new(spaces: Vec<I>) -> Self {
+        let mut seen = HashSet::new();
+        let table = spaces
+            .iter()
+            .enumerate()
+            .flat_map(|(s, v)| (0..v.len()).map(move |i| (s, i)))
+            .filter(|(s, i)| seen.insert(spaces[*s].get(*i)))
+            .collect();
+        Self { spaces, table }
+    }
+}
+impl<I> Indexing for UnionVec<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.table.len()
+    }
+    fn get(&self, at: usize) -> I::Item {
+        let (s, i) = self.table[at];
+        self.spaces[s].get(i)
+    }
+}
+
+/// Iterates the orderings of `0..n`, in lexicographic order
+#[derive(Clone)]
+pub struct Permutations {
+    n: usize,
+}
+impl Permutations {
+    pub fn new(n: usize) -> Self {
+        Self { n }
+    }
+}
+impl Indexing for Permutations {
+    type Item = Vec<usize>;
+    fn len(&self) -> usize {
+        number_encoding::factorial(self.n)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        let mut r: Vec<usize> = (0..self.n).collect();
+        number_encoding::factoradics::decode_mut(&mut r, at);
+        r
+    }
+}
+impl InverseIndexing for Permutations {
+    fn index_of(&self, item: &Vec<usize>) -> Option<usize> {
+        let mut seen = vec![false; self.n];
+        for e in item.iter() {
+            if *e >= self.n || std::mem::replace(&mut seen[*e], true) {
+                return None;
+            }
+        }
+        (item.len() == self.n).then(|| number_encoding::factoradics::encode(item))
+    }
+}
+impl IndexingInto for Permutations {
+    type Buf = [usize];
+    fn get_into(&self, at: usize, out: &mut [usize]) {
+        assert_eq!(out.len(), self.n, "the buffer should be n long");
+        for (i, e) in out.iter_mut().enumerate() {
+            *e = i;
+        }
+        number_encoding::factoradics::decode_mut(out, at);
+    }
+}
+
+/// Every combination of one item from each of the spaces, like a `Cross` over any number of axes of the same type. The last axis changes fastest.
+#[derive(Clone)]
+pub struct CrossVec<I>(pub Vec<I>);
+impl<I> Indexing for CrossVec<I>
+where
+    I: Indexing,
+{
+    type Item = Vec<I::Item>;
+    fn len(&self) -> usize {
+        self.0.iter().map(|a| a.len()).product()
+    }
+    fn get(&self, mut at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let mut r: Vec<I::Item> = self
+            .0
+            .iter()
+            .rev()
+            .map(|a| {
+                let l = a.len();
+                let e = a.get(at % l);
+                at /= l;
+                e
+            })
+            .collect();
+        r.reverse();
+        r
+    }
+}
+impl<I> InverseIndexing for CrossVec<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        if item.len() != self.0.len() {
+            return None;
+        }
+        self.0
+            .iter()
+            .zip(item.iter())
+            .try_fold(0, |acc, (a, e)| Some(acc * a.len() + a.index_of(e)?))
+    }
+}
+/// an odometer, the last axis turning fastest
+impl<I> Successor for CrossVec<I>
+where
+    I: Successor,
+{
+    fn advance(&self, mut at: usize, item: &mut Self::Item) {
+        for (a, e) in self.0.iter().zip(item.iter_mut()).rev() {
+            let l = a.len();
+            if at % l + 1 < l {
+                a.advance(at % l, e);
+                return;
+            }
+            *e = a.get(0);
+            at /= l;
+        }
+    }
+}
+
+/// `Cross` in reflected Gray order: B runs forwards for even items of A and backwards for odd ones, so consecutive pairs differ in only one of the two
+#[derive(Clone)]
+pub struct GrayCross<A, B>(pub A, pub B);
+impl<A, B> Indexing for GrayCross<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    type Item = (A::Item, B::Item);
+    fn len(&self) -> usize {
+        self.0.len() * self.1.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let lb = self.1.len();
+        let (i, j) = (at / lb, at % lb);
+        let j = if i % 2 == 1 { lb - 1 - j } else { j };
+        (self.0.get(i), self.1.get(j))
+    }
+}
+impl<A, B> InverseIndexing for GrayCross<A, B>
+where
+    A: InverseIndexing,
+    B: InverseIndexing,
+{
+    fn index_of(&self, (a, b): &Self::Item) -> Option<usize> {
+        let (i, j) = (self.0.index_of(a)?, self.1.index_of(b)?);
+        let lb = self.1.len();
+        Some(i * lb + if i % 2 == 1 { lb - 1 - j } else { j })
+    }
+}
+
+/// `CrossVec` in reflected mixed-radix Gray order, so consecutive items differ in only one axis, by one step. Each axis reverses direction whenever the axes before it move.
+#[derive(Clone)]
+pub struct GrayCrossVec<I>(pub Vec<I>);
+impl<I> Indexing for GrayCrossVec<I>
+where
+    I: Indexing,
+{
+    type Item = Vec<I::Item>;
+    fn len(&self) -> usize {
+        self.0.iter().map(|a| a.len()).product()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        // `below` is how many items each value of this axis spans
+        let mut below = self.len();
+        self.0
+            .iter()
+            .map(|a| {
+                let l = a.len();
+                below /= l;
+                // the number formed by this axis and the ones before it
+                let prefix = at / below;
+                let d = prefix % l;
+                let reflected = (prefix / l) % 2 == 1;
+                a.get(if reflected { l - 1 - d } else { d })
+            })
+            .collect()
+    }
+}
+impl<I> InverseIndexing for GrayCrossVec<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        if item.len() != self.0.len() {
+            return None;
+        }
+        self.0
+            .iter()
+            .zip(item.iter())
+            .try_fold(0, |prefix, (a, e)| {
+                let (l, g) = (a.len(), a.index_of(e)?);
+                let d = if prefix % 2 == 1 { l - 1 - g } else { g };
+                Some(prefix * l + d)
+            })
+    }
+}
+
+/// Like `Cross`, but goes out in diagonals, (0, 0), then (0, 1), (1, 0), then (0, 2), (1, 1), (2, 0)..., so that every prefix has explored small indices of both axes rather than just the first few of A. Diagonals are clipped to the lengths of A and B.
+#[derive(Clone)]
+pub struct DiagonalCross<A, B>(pub A, pub B);
+impl<A, B> DiagonalCross<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    /// how many pairs lie before diagonal d, ie, have i + j < d. Inclusion-exclusion over the unbounded triangle.
+    fn before(&self, d: usize) -> usize {
+        let t = |x: i128| if x > 0 { x * (x + 1) / 2 } else { 0 };
+        let (d, la, lb) = (d as i128, self.0.len() as i128, self.1.len() as i128);
+        (t(d) - t(d - la) - t(d - lb) + t(d - la - lb)) as usize
+    }
+    /// where diagonal d enters A
+    fn first_i(&self, d: usize) -> usize {
+        (d + 1).saturating_sub(self.1.len())
+    }
+}
+impl<A, B> Indexing for DiagonalCross<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    type Item = (A::Item, B::Item);
+    fn len(&self) -> usize {
+        self.0.len() * self.1.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let diagonals = self.0.len() + self.1.len() - 1;
+        let d = partition_point_to(diagonals, |d| self.before(d + 1) <= at);
+        let i = self.first_i(d) + at - self.before(d);
+        (self.0.get(i), self.1.get(d - i))
+    }
+}
+impl<A, B> InverseIndexing for DiagonalCross<A, B>
+where
+    A: InverseIndexing,
+    B: InverseIndexing,
+{
+    fn index_of(&self, (a, b): &Self::Item) -> Option<usize> {
+        let (i, j) = (self.0.index_of(a)?, self.1.index_of(b)?);
+        Some(self.before(i + j) + i - self.first_i(i + j))
+    }
+}
+
+/// Like `Cross`, but goes out in square shells, every pair with max(i, j) = 0, then 1, then 2..., so any prefix covers a square (as far as the axes allow) of both axes. Within shell s, the (s, j) come first, then the (i, s).
+#[derive(Clone)]
+pub struct ShellCross<A, B>(pub A, pub B);
+impl<A, B> ShellCross<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    /// how many pairs lie inside shell s
+    fn before(&self, s: usize) -> usize {
+        s.min(self.0.len()) * s.min(self.1.len())
+    }
+    /// how many of shell s's pairs have i = s
+    fn row(&self, s: usize) -> usize {
+        if s < self.0.len() {
+            (s + 1).min(self.1.len())
+        } else {
+            0
+        }
+    }
+}
+impl<A, B> Indexing for ShellCross<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    type Item = (A::Item, B::Item);
+    fn len(&self) -> usize {
+        self.0.len() * self.1.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let shells = self.0.len().max(self.1.len());
+        let s = partition_point_to(shells, |s| self.before(s + 1) <= at);
+        let offset = at - self.before(s);
+        let row = self.row(s);
+        if offset < row {
+            (self.0.get(s), self.1.get(offset))
+        } else {
+            (self.0.get(offset - row), self.1.get(s))
+        }
+    }
+}
+impl<A, B> InverseIndexing for ShellCross<A, B>
+where
+    A: InverseIndexing,
+    B: InverseIndexing,
+{
+    fn index_of(&self, (a, b): &Self::Item) -> Option<usize> {
+        let (i, j) = (self.0.index_of(a)?, self.1.index_of(b)?);
+        let s = i.max(j);
+        Some(if i == s {
+            self.before(s) + j
+        } else {
+            self.before(s) + self.row(s) + i
+        })
+    }
+}
+
+/// `CrossVec`, but ordered by level, the sum of the indices into each axis, so every prefix has explored small indices of every axis. Within a level, the order is lexicographic. Keeps a table of counts with an entry per axis per level.
+#[derive(Clone)]
+pub struct LevelCross<I> {
+    axes: Vec<I>,
+    /// `counts[a][s]`: how many ways axes `a..` can have indices summing to s
+    counts: Vec<Vec<usize>>,
+    /// where each level starts
+    starts: Vec<usize>,
+}
+impl<I> LevelCross<I>
+where
+    I: Indexing,
+{
+    pub fn new(axes: Vec<I>) -> Self {
+        let lens: Vec<usize> = axes.iter().map(|a| a.len()).collect();
+        let top: usize = if lens.contains(&0) {
+            0
+        } else {
+            lens.iter().map(|l| l - 1).sum()
+        };
+        let mut counts = vec![vec![0; top + 1]; lens.len() + 1];
+        counts[lens.len()][0] = 1;
+        for a in (0..lens.len()).rev() {
+            for s in 0..=top {
+                counts[a][s] = (0..lens[a].min(s + 1)).map(|c| counts[a + 1][s - c]).sum();
+            }
+        }
+        let mut starts = vec![0];
+        for s in 0..=top {
+            starts.push(starts[s] + counts[0][s]);
+        }
+        Self {
+            axes,
+            counts,
+            starts,
+        }
+    }
+}
+impl<I> Indexing for LevelCross<I>
+where
+    I: Indexing,
+{
+    type Item = Vec<I::Item>;
+    fn len(&self) -> usize {
+        *self.starts.last().unwrap()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let mut level = self.starts.partition_point(|s| *s <= at) - 1;
+        let mut offset = at - self.starts[level];
+        self.axes
+            .iter()
+            .enumerate()
+            .map(|(a, axis)| {
+                let mut c = 0;
+                while offset >= self.counts[a + 1][level - c] {
+                    offset -= self.counts[a + 1][level - c];
+                    c += 1;
+                }
+                level -= c;
+                axis.get(c)
+            })
+            .collect()
+    }
+}
+impl<I> InverseIndexing for LevelCross<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        if item.len() != self.axes.len() {
+            return None;
+        }
+        let coords: Vec<usize> = self
+            .axes
+            .iter()
+            .zip(item.iter())
+            .map(|(axis, e)| axis.index_of(e))
+            .collect::<Option<_>>()?;
+        let mut level: usize = coords.iter().sum();
+        let mut r = self.starts[level];
+        for (a, c) in coords.iter().enumerate() {
+            r += (0..*c)
+                .map(|b| self.counts[a + 1][level - b])
+                .sum::<usize>();
+            level -= c;
+        }
+        Some(r)
+    }
+}
+
+/// steps a combination to the next in colex order, the order `combinadics` numbers them in: bumps the lowest element that has room, and resets the ones below it. Amortized O(1). Returns false for the empty combination, which has no next.
+fn next_combination(c: &mut [usize]) -> bool {
+    let k = c.len();
+    match (0..k).find(|i| *i + 1 == k || c[i + 1] > c[*i] + 1) {
+        Some(i) => {
+            c[i] += 1;
+            for (j, e) in c[..i].iter_mut().enumerate() {
+                *e = j;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// the same for a multiset, which is a combination with each element lowered by its position: raises them, steps that, and lowers them again. O(k).
+fn next_multiset(m: &mut [usize]) -> bool {
+    for (i, e) in m.iter_mut().enumerate() {
+        *e += i;
+    }
+    let stepped = next_combination(m);
+    for (i, e) in m.iter_mut().enumerate() {
+        *e -= i;
+    }
+    stepped
+}
+
+/// Iterates k-sized subsets of the n-sized input set
+#[derive(Clone)]
+pub struct KSubsets {
+    n: usize,
+    k: usize,
+}
+impl KSubsets {
+    pub fn new(n: usize, k: usize) -> Self {
+        Self { n, k }
+    }
+}
+impl Indexing for KSubsets {
+    type Item = Vec<usize>;
+    fn len(&self) -> usize {
+        number_encoding::combination(self.n, self.k)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        number_encoding::combinadics::decode(at, self.k)
+    }
+}
+impl IndexingInto for KSubsets {
+    type Buf = [usize];
+    fn get_into(&self, at: usize, out: &mut [usize]) {
+        assert_eq!(out.len(), self.k, "the buffer should be k long");
+        number_encoding::combinadics::decode_mut(at, self.k, out);
+    }
+}
+impl InverseIndexing for KSubsets {
+    fn index_of(&self, item: &Vec<usize>) -> Option<usize> {
+        let increasing = item.windows(2).all(|w| w[0] < w[1]);
+        (item.len() == self.k && increasing && item.iter().all(|e| *e < self.n))
+            .then(|| number_encoding::combinadics::encode(item))
+    }
+}
+impl Successor for KSubsets {
+    fn advance(&self, _at: usize, item: &mut Vec<usize>) {
+        next_combination(item);
+    }
+}
+
+/// Iterates the same subsets as `KSubsets`, in revolving door order, where each subset differs from the last by one element going out and one coming in. Unranking follows Kreher and Stinson.
+#[derive(Clone)]
+pub struct RevolvingDoorSubsets {
+    n: usize,
+    k: usize,
+}
+impl RevolvingDoorSubsets {
+    pub fn new(n: usize, k: usize) -> Self {
+        Self { n, k }
+    }
+}
+impl Indexing for RevolvingDoorSubsets {
+    type Item = Vec<usize>;
+    fn len(&self) -> usize {
+        number_encoding::combination(self.n, self.k)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let mut r = at;
+        let mut x = self.n;
+        let mut t = vec![0; self.k];
+        for i in (1..=self.k).rev() {
+            while number_encoding::combination(x, i) > r {
+                x -= 1;
+            }
+            t[i - 1] = x;
+            r = number_encoding::combination(x + 1, i) - r - 1;
+        }
+        t
+    }
+}
+impl InverseIndexing for RevolvingDoorSubsets {
+    fn index_of(&self, item: &Vec<usize>) -> Option<usize> {
+        let increasing = item.windows(2).all(|w| w[0] < w[1]);
+        if item.len() != self.k || !increasing || item.iter().any(|e| *e >= self.n) {
+            return None;
+        }
+        // alternating sum of C(t_i + 1, i), from the largest element down
+        let mut r = -((self.k % 2) as i128);
+        let mut sign = 1;
+        for i in (1..=self.k).rev() {
+            r += sign * number_encoding::combination(item[i - 1] + 1, i) as i128;
+            sign = -sign;
+        }
+        Some(r as usize)
+    }
+}
+
+/// Iterates k-sized multiset (where entries are allowed to repeat) subsets of the n-sized input set
+#[derive(Clone)]
+pub struct KSubmultisets {
+    n: usize,
+    k: usize,
+}
+impl KSubmultisets {
+    pub fn new(n: usize, k: usize) -> Self {
+        Self { n, k }
+    }
+}
+impl Indexing for KSubmultisets {
+    type Item = Vec<usize>;
+    fn len(&self) -> usize {
+        number_encoding::combination(self.n + self.k - 1, self.k)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        let mut r = number_encoding::combinadics::decode(at, self.k);
+        for (i, v) in r.iter_mut().enumerate() {
+            *v -= i
+        }
+        r
+    }
+}
+impl IndexingInto for KSubmultisets {
+    type Buf = [usize];
+    fn get_into(&self, at: usize, out: &mut [usize]) {
+        assert_eq!(out.len(), self.k, "the buffer should be k long");
+        number_encoding::combinadics::decode_mut(at, self.k, out);
+        for (i, v) in out.iter_mut().enumerate() {
+            *v -= i
+        }
+    }
+}
+impl InverseIndexing for KSubmultisets {
+    fn index_of(&self, item: &Vec<usize>) -> Option<usize> {
+        let nondecreasing = item.windows(2).all(|w| w[0] <= w[1]);
+        if item.len() != self.k || !nondecreasing || item.iter().any(|e| *e >= self.n) {
+            return None;
+        }
+        let spread: Vec<usize> = item.iter().enumerate().map(|(i, v)| v + i).collect();
+        Some(number_encoding::combinadics::encode(&spread))
+    }
+}
+impl Successor for KSubmultisets {
+    fn advance(&self, _at: usize, item: &mut Vec<usize>) {
+        next_multiset(item);
+    }
+}
+
+/// `KSubsets` with k fixed at compile time, so items are arrays and never allocate
+#[derive(Clone, Copy)]
+pub struct KSubsetsN<const K: usize> {
+    n: usize,
+}
+impl<const K: usize> KSubsetsN<K> {
+    pub fn new(n: usize) -> Self {
+        Self { n }
+    }
+}
+impl<const K: usize> Indexing for KSubsetsN<K> {
+    type Item = [usize; K];
+    fn len(&self) -> usize {
+        number_encoding::combination(self.n, K)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        let mut r = [0; K];
+        number_encoding::combinadics::decode_mut(at, K, &mut r);
+        r
+    }
+}
+impl<const K: usize> InverseIndexing for KSubsetsN<K> {
+    fn index_of(&self, item: &[usize; K]) -> Option<usize> {
+        let increasing = item.windows(2).all(|w| w[0] < w[1]);
+        (increasing && item.iter().all(|e| *e < self.n))
+            .then(|| number_encoding::combinadics::encode(item))
+    }
+}
+impl<const K: usize> Successor for KSubsetsN<K> {
+    fn advance(&self, _at: usize, item: &mut [usize; K]) {
+        next_combination(item);
+    }
+}
+
+/// `KSubmultisets` with k fixed at compile time, so items are arrays and never allocate
+#[derive(Clone, Copy)]
+pub struct KSubmultisetsN<const K: usize> {
+    n: usize,
+}
+impl<const K: usize> KSubmultisetsN<K> {
+    pub fn new(n: usize) -> Self {
+        Self { n }
+    }
+}
+impl<const K: usize> Indexing for KSubmultisetsN<K> {
+    type Item = [usize; K];
+    fn len(&self) -> usize {
+        number_encoding::combination(self.n + K - 1, K)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        let mut r = [0; K];
+        number_encoding::combinadics::decode_mut(at, K, &mut r);
+        for (i, v) in r.iter_mut().enumerate() {
+            *v -= i
+        }
+        r
+    }
+}
+impl<const K: usize> InverseIndexing for KSubmultisetsN<K> {
+    fn index_of(&self, item: &[usize; K]) -> Option<usize> {
+        let nondecreasing = item.windows(2).all(|w| w[0] <= w[1]);
+        if !nondecreasing || item.iter().any(|e| *e >= self.n) {
+            return None;
+        }
+        let mut spread = *item;
+        for (i, v) in spread.iter_mut().enumerate() {
+            *v += i
+        }
+        Some(number_encoding::combinadics::encode(&spread))
+    }
+}
+impl<const K: usize> Successor for KSubmultisetsN<K> {
+    fn advance(&self, _at: usize, item: &mut [usize; K]) {
+        next_multiset(item);
+    }
+}
+
+#[derive(Clone)]
+pub struct IndexVec<T> (pub Vec<T>);
+impl<T> Indexing for IndexVec<T> where T:Clone {
+    type Item = T;
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        self.0[at].clone()
+    }
+}
+/// a linear search
+impl<T> InverseIndexing for IndexVec<T>
+where
+    T: Clone + PartialEq,
+{
+    fn index_of(&self, item: &T) -> Option<usize> {
+        self.0.iter().position(|e| e == item)
+    }
+}
+
+pub struct Truncate<I>(pub usize, pub I);
+impl<I> Indexing for Truncate<I> where I:Indexing {
+    type Item=I::Item;
+
+    fn len(&self) -> usize {
+        self.0.min(self.1.len())
+    }
+
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        self.1.get(at)
+    }
+}
+impl<I> InverseIndexing for Truncate<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        self.1.index_of(item).filter(|i| *i < self.0)
+    }
+}
+
+/// Pairs each item a of A with every item of the space `f(a)`, for spaces that aren't rectangular, like pairs `(n, k)` with `k <= n`. `f` is called on every item of A once when constructing, to find the lengths of the inner spaces, and then again with each `get`.
+#[derive(Clone)]
+pub struct DependentCross<A, F> {
+    a: A,
+    f: F,
+    /// prefix[i] is the index of the first pair with A's ith item
+    prefix: Vec<usize>,
+}
+impl<A, F, B> DependentCross<A, F>
+where
+    A: Indexing,
+    F: Fn(&A::Item) -> B,
+    B: Indexing,
+{
+    pub fn new(a: A, f: F) -> Self {
+        let mut prefix = Vec::with_capacity(a.len() + 1);
+        let mut total = 0;
+        prefix.push(0);
+        for i in 0..a.len() {
+            total += f(&a.get(i)).len();
+            prefix.push(total);
+        }
+        Self { a, f, prefix }
+    }
+}
+impl<A, F, B> Indexing for DependentCross<A, F>
+where
+    A: Indexing,
+    F: Fn(&A::Item) -> B,
+    B: Indexing,
+{
+    type Item = (A::Item, B::Item);
+    fn len(&self) -> usize {
+        *self.prefix.last().unwrap()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let i = self.prefix.partition_point(|p| *p <= at) - 1;
+        let a = self.a.get(i);
+        let b = (self.f)(&a).get(at - self.prefix[i]);
+        (a, b)
+    }
+}
+impl<A, F, B> InverseIndexing for DependentCross<A, F>
+where
+    A: InverseIndexing,
+    F: Fn(&A::Item) -> B,
+    B: InverseIndexing,
+{
+    fn index_of(&self, (a, b): &Self::Item) -> Option<usize> {
+        let i = self.a.index_of(a)?;
+        Some(self.prefix[i] + (self.f)(a).index_of(b)?)
+    }
+}
+
+/// The pairs `(i, j)` with `j <= i < n`, in the same order as `DependentCross::new(0..n, |i| 0..i + 1)` but in constant time and space
+#[derive(Clone, Copy)]
+pub struct Triangle(pub usize);
+impl Indexing for Triangle {
+    type Item = (usize, usize);
+    fn len(&self) -> usize {
+        self.0 * (self.0 + 1) / 2
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let i = (((8 * at as u128 + 1).isqrt() - 1) / 2) as usize;
+        (i, at - i * (i + 1) / 2)
+    }
+}
+impl InverseIndexing for Triangle {
+    fn index_of(&self, &(i, j): &(usize, usize)) -> Option<usize> {
+        (j <= i && i < self.0).then(|| i * (i + 1) / 2 + j)
+    }
+}
+
+/// The items of I at indices within the range (as much of the range as I has)
+#[derive(Clone)]
+pub struct Window<I>(pub Range<usize>, pub I);
+impl<I> Indexing for Window<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.0.end.min(self.1.len()).saturating_sub(self.0.start)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        self.1.get(self.0.start + at)
+    }
+}
+impl<I> InverseIndexing for Window<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        let j = self.1.index_of(item)?;
+        (j >= self.0.start && j - self.0.start < self.len()).then(|| j - self.0.start)
+    }
+}
+
+/// All but the first n items of I
+#[derive(Clone)]
+pub struct Skip<I>(pub usize, pub I);
+impl<I> Indexing for Skip<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.1.len().saturating_sub(self.0)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        self.1.get(self.0 + at)
+    }
+}
+impl<I> InverseIndexing for Skip<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        self.1.index_of(item)?.checked_sub(self.0)
+    }
+}
+
+/// Every kth item of I, starting with the first
+#[derive(Clone)]
+pub struct StepBy<I>(pub usize, pub I);
+impl<I> Indexing for StepBy<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        assert!(self.0 > 0, "can't step by 0");
+        self.1.len().div_ceil(self.0)
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        self.1.get(at * self.0)
+    }
+}
+impl<I> InverseIndexing for StepBy<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        let j = self.1.index_of(item)?;
+        (j % self.0 == 0).then_some(j / self.0)
+    }
+}
+
+/// I backwards
+#[derive(Clone)]
+pub struct Rev<I>(pub I);
+impl<I> Indexing for Rev<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        self.0.get(self.0.len() - 1 - at)
+    }
+}
+impl<I> InverseIndexing for Rev<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        Some(self.0.len() - 1 - self.0.index_of(item)?)
+    }
+}
+
+/// All of I, `times` times over
+#[derive(Clone)]
+pub struct Repeat<I>(pub usize, pub I);
+impl<I> Indexing for Repeat<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.0 * self.1.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        self.1.get(at % self.1.len())
+    }
+}
+/// finds the first repetition
+impl<I> InverseIndexing for Repeat<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        self.1.index_of(item).filter(|_| self.0 > 0)
+    }
+}
+
+/// Pairs up the items of A and B at the same index, as long as both have one
+#[derive(Clone)]
+pub struct Zip<A, B>(pub A, pub B);
+impl<A, B> Indexing for Zip<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    type Item = (A::Item, B::Item);
+    fn len(&self) -> usize {
+        self.0.len().min(self.1.len())
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        (self.0.get(at), self.1.get(at))
+    }
+}
+impl<A, B> InverseIndexing for Zip<A, B>
+where
+    A: InverseIndexing,
+    B: Indexing,
+    B::Item: PartialEq,
+{
+    fn index_of(&self, (a, b): &Self::Item) -> Option<usize> {
+        let i = self.0.index_of(a)?;
+        (i < self.len() && self.1.get(i) == *b).then_some(i)
+    }
+}
+
+/// Yields each item with its index, so that, say, consumers of a shuffled space can log where in the original space each item came from
+#[derive(Clone)]
+pub struct Enumerate<I>(pub I);
+impl<I> Indexing for Enumerate<I>
+where
+    I: Indexing,
+{
+    type Item = (usize, I::Item);
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        (at, self.0.get(at))
+    }
+}
+impl<I> InverseIndexing for Enumerate<I>
+where
+    I: Indexing,
+    I::Item: PartialEq,
+{
+    fn index_of(&self, (i, item): &Self::Item) -> Option<usize> {
+        (*i < self.len() && self.0.get(*i) == *item).then_some(*i)
+    }
+}
+
+/// `None`, then `Some` of each item of I. For axes that may be left out.
+#[derive(Clone)]
+pub struct Optional<I>(pub I);
+impl<I> Indexing for Optional<I>
+where
+    I: Indexing,
+{
+    type Item = Option<I::Item>;
+    fn len(&self) -> usize {
+        self.0.len() + 1
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        at.checked_sub(1).map(|i| self.0.get(i))
+    }
+}
+impl<I> InverseIndexing for Optional<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        match item {
+            None => Some(0),
+            Some(e) => Some(self.0.index_of(e)? + 1),
+        }
+    }
+}
+
+/// How a `Shard` divides its space
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShardMode {
+    /// shard i gets i, i + n, i + 2n...
+    Strided,
+    /// shard i gets one unbroken run of the space
+    Contiguous,
+}
+
+/// One of `count` disjoint, near-equal parts of a space (the lengths of the shards differ by at most one). Together the shards cover every item of the space exactly once, so separate workers can each take a shard without coordinating.
+/// ```rust
+/// // the first 10% of the shuffled space, split over 8 workers
+/// let space = light_shuffle(Cross(0..1000, 0..1000));
+/// let mine = Shard::new(Truncate(space.len() / 10, space), worker_index, 8);
+/// ```
+#[derive(Clone)]
+pub struct Shard<I> {
+    v: I,
+    index: usize,
+    count: usize,
+    mode: ShardMode,
+}
+impl<I> Shard<I> {
+    /// a strided shard
+    pub fn new(v: I, index: usize, count: usize) -> Self {
+        Self::with_mode(v, index, count, ShardMode::Strided)
+    }
+    pub fn with_mode(v: I, index: usize, count: usize, mode: ShardMode) -> Self {
+        assert!(index < count, "shard {index} of {count} doesn't exist");
+        Self {
+            v,
+            index,
+            count,
+            mode,
+        }
+    }
+    /// where this shard's part starts in the underlying space, for contiguous shards
+    fn boundary(&self, index: usize, total: usize) -> usize {
+        (index as u128 * total as u128 / self.count as u128) as usize
+    }
+}
+impl<I> Indexing for Shard<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        let total = self.v.len();
+        match self.mode {
+            ShardMode::Strided => (total + self.count - 1 - self.index) / self.count,
+            ShardMode::Contiguous => {
+                self.boundary(self.index + 1, total) - self.boundary(self.index, total)
+            }
+        }
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        match self.mode {
+            ShardMode::Strided => self.v.get(at * self.count + self.index),
+            ShardMode::Contiguous => self.v.get(self.boundary(self.index, self.v.len()) + at),
+        }
+    }
+}
+impl<I> InverseIndexing for Shard<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        let j = self.v.index_of(item)?;
+        match self.mode {
+            ShardMode::Strided => {
+                (j % self.count == self.index).then_some(j / self.count)
+            }
+            ShardMode::Contiguous => {
+                let start = self.boundary(self.index, self.v.len());
+                (start..start + self.len()).contains(&j).then(|| j - start)
+            }
+        }
+    }
+}
+
+/// psuedorandomly permutes the given Indexing
+/// ```rust
+/// Shuffled::<_, rng::DefaultShuffler>::new(Cross(0..3, 0..2))
+/// ```
+#[derive(Clone)]
+pub struct Shuffled<D, S> {
+    v: D,
+    r: S,
+}
+impl<D, S> Shuffled<D, S> {
+    pub fn new(v: D) -> Shuffled<D, S>
+    where
+        D: Indexing,
+        S: Shuffler + FromLength,
+    {
+        let r = S::for_length(v.len());
+        Self::with_shuffler(v, r)
+    }
+    /// for shufflers that were built some other way, eg, a `Box<dyn Shuffler>`. `r` must have a period of at least `v.len()`.
+    pub fn with_shuffler(v: D, r: S) -> Shuffled<D, S>
+    where
+        D: Indexing,
+        S: Shuffler,
+    {
+        assert!(
+            r.period() >= v.len() as u64,
+            "the shuffler's period is shorter than the space"
+        );
+        Self { v, r }
+    }
+}
+impl<D> Shuffled<D, Seeded<DynShuffler>> {
+    /// shuffles with the algorithm, rounds and seed given in `config`
+    pub fn with_config(v: D, config: &ShufflerConfig) -> Self
+    where
+        D: Indexing,
+    {
+        let r = config.for_length(v.len());
+        Self::with_shuffler(v, r)
+    }
+}
+impl<D, S> Indexing for Shuffled<D, S>
+where
+    D: Indexing,
     S: Shuffler
 {
-    type Item = D::Item;
+    type Item = D::Item;
+    fn len(&self) -> usize {
+        self.v.len()
+    }
+    fn get(&self, at: usize) -> D::Item {
+        self.v
+            .get(rng::permute(&self.r, self.v.len() as u64, at as u64) as usize)
+    }
+}
+
+impl<D, S> IndexingInto for Shuffled<D, S>
+where
+    D: IndexingInto,
+    S: Shuffler,
+{
+    type Buf = D::Buf;
+    fn get_into(&self, at: usize, out: &mut Self::Buf) {
+        let i = rng::permute(&self.r, self.v.len() as u64, at as u64) as usize;
+        self.v.get_into(i, out)
+    }
+}
+
+/// finds where an item landed in the shuffled order, by walking the shuffler backwards
+impl<D, S> InverseIndexing for Shuffled<D, S>
+where
+    D: InverseIndexing,
+    S: rng::InvertibleShuffler,
+{
+    fn index_of(&self, item: &D::Item) -> Option<usize> {
+        let j = self.v.index_of(item)?;
+        Some(rng::unpermute(&self.r, self.v.len() as u64, j as u64) as usize)
+    }
+}
+
+pub fn light_shuffle<D>(d:D)-> Shuffled<D, DefaultShuffler> where D:Indexing {
+    Shuffled::<D, DefaultShuffler>::new(d)
+}
+// pub fn heavy_shuffle()-> Shuffled<D, CipherShuffler>
+
+/// Spaces whose items are the points of a grid, with `get` going through it row by row (the last axis changing fastest), see `LowDiscrepancy`
+pub trait Grid: Indexing {
+    /// the length of each axis
+    fn axes(&self) -> Vec<usize>;
+}
+impl Grid for Range<usize> {
+    fn axes(&self) -> Vec<usize> {
+        vec![Indexing::len(self)]
+    }
+}
+impl<A, B> Grid for Cross<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    fn axes(&self) -> Vec<usize> {
+        vec![self.0.len(), self.1.len()]
+    }
+}
+impl<I> Grid for CrossVec<I>
+where
+    I: Indexing,
+{
+    fn axes(&self) -> Vec<usize> {
+        self.0.iter().map(|a| a.len()).collect()
+    }
+}
+
+/// how many of `0..l` are congruent to c mod 2^t
+fn residue_count(l: usize, c: usize, t: u32) -> usize {
+    if c >= l {
+        0
+    } else if t >= usize::BITS {
+        1
+    } else {
+        ((l - c - 1) >> t) + 1
+    }
+}
+
+/// Visits the points of a grid coarse to fine, so that wherever you cut it off, what's been visited is spread evenly over the grid. Each axis goes in bit-reversed (van der Corput) order, and the axes take turns at each level of refinement, so for power of two axes the first 2^d points of a d dimensional grid hit each of its 2^d quadrants, the first 4^d hit each of the 4^d subquadrants, and so on. Axes that aren't powers of two just skip the points past their end, so it's a permutation all the same.
+/// ```rust
+/// // preview renders that sharpen evenly instead of filling in top to bottom
+/// for (y, x) in LowDiscrepancy::scrambled(Cross(0..height, 0..width), seed).iter() {
+///     render_pixel(x, y);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct LowDiscrepancy<G> {
+    v: G,
+    scramble: Option<u64>,
+}
+impl<G> LowDiscrepancy<G>
+where
+    G: Grid,
+{
+    pub fn new(v: G) -> Self {
+        Self { v, scramble: None }
+    }
+    /// randomizes which half of each subdivision goes first (a base 2 nested scramble), so different seeds give different orders that are just as evenly spread
+    pub fn scrambled(v: G, seed: u64) -> Self {
+        Self {
+            v,
+            scramble: Some(seed),
+        }
+    }
+    /// whether the odd half goes first when splitting the points of `axis` congruent to c mod 2^t
+    fn flip(&self, axis: usize, t: u32, c: usize) -> usize {
+        self.scramble.map_or(0, |seed| {
+            let cell = rng::splitmix64(c as u64) ^ ((axis as u64) << 40 | t as u64);
+            (rng::splitmix64(seed ^ rng::splitmix64(cell)) & 1) as usize
+        })
+    }
+    /// narrows the grid down to one point, one bit of one axis at a time. Axes needing fewer bits start later, so that every axis decides its top bit on the last level. `first_half(axis, bit, flip, count)` is told how many points are in the half that comes first, and says whether to go into it.
+    fn descend(
+        &self,
+        lens: &[usize],
+        mut first_half: impl FnMut(usize, u32, usize, usize) -> bool,
+    ) -> Vec<usize> {
+        let bits: Vec<u32> = lens
+            .iter()
+            .map(|l| {
+                if *l <= 1 {
+                    0
+                } else {
+                    usize::BITS - (l - 1).leading_zeros()
+                }
+            })
+            .collect();
+        let levels = bits.iter().copied().max().unwrap_or(0);
+        let mut c = vec![0; lens.len()];
+        // how many bits of each axis have been decided
+        let mut depth = vec![0; lens.len()];
+        for t in 0..levels {
+            for a in 0..lens.len() {
+                if t < levels - bits[a] {
+                    continue;
+                }
+                let d = depth[a];
+                if residue_count(lens[a], c[a], d) > 1 {
+                    let others: usize = (0..lens.len())
+                        .filter(|b| *b != a)
+                        .map(|b| residue_count(lens[b], c[b], depth[b]))
+                        .product();
+                    let flip = self.flip(a, d, c[a]);
+                    let count = others * residue_count(lens[a], c[a] + (flip << d), d + 1);
+                    let bit = if first_half(a, d, flip, count) {
+                        flip
+                    } else {
+                        1 - flip
+                    };
+                    c[a] += bit << d;
+                }
+                depth[a] += 1;
+            }
+        }
+        c
+    }
+}
+impl<G> Indexing for LowDiscrepancy<G>
+where
+    G: Grid,
+{
+    type Item = G::Item;
     fn len(&self) -> usize {
         self.v.len()
     }
-    fn get(&self, at: usize) -> D::Item {
-        let st = self.v.len() as u64;
-        let mut n = self.r.output_to_state(at as u64);
-        loop {
-            n = self.r.next(n as u64) as u64;
-            if self.r.state_to_output(n) < st {
-                break;
+    fn get(&self, mut at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let lens = self.v.axes();
+        let coords = self.descend(&lens, |_, _, _, count| {
+            if at < count {
+                true
+            } else {
+                at -= count;
+                false
             }
+        });
+        let flat = lens
+            .iter()
+            .zip(coords.iter())
+            .fold(0, |f, (l, c)| f * l + c);
+        self.v.get(flat)
+    }
+}
+impl<G> InverseIndexing for LowDiscrepancy<G>
+where
+    G: Grid + InverseIndexing,
+{
+    fn index_of(&self, item: &Self::Item) -> Option<usize> {
+        let lens = self.v.axes();
+        let mut flat = self.v.index_of(item)?;
+        let mut coords = vec![0; lens.len()];
+        for (c, l) in coords.iter_mut().zip(lens.iter()).rev() {
+            *c = flat % l;
+            flat /= l;
         }
-        self.v.get(self.r.state_to_output(n) as usize)
+        let mut r = 0;
+        self.descend(&lens, |a, t, flip, count| {
+            let first = (coords[a] >> t) & 1 == flip;
+            if !first {
+                r += count;
+            }
+            first
+        });
+        Some(r)
     }
 }
 
-pub fn light_shuffle<D>(d:D)-> Shuffled<D, DefaultShuffler> where D:Indexing {
-    Shuffled::<D, DefaultShuffler>::new(d)
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// the inverse of a mod m, for coprime a and m
+fn mod_inverse(a: usize, m: usize) -> usize {
+    let (mut r0, mut r1) = (a as i128, m as i128);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    s0.rem_euclid(m as i128) as usize
+}
+
+/// A shuffle of a `Cross` where every run of `len(A)` outputs (starting from a multiple of `len(A)`) has each item of A exactly once, and every such run of `len(B)` outputs has each item of B once, like a latin hypercube sample. So a prefix never neglects any value of either axis for longer than an axis length, which a plain shuffle can.
+///
+/// Underneath, output p is `(p mod a, (p + p / lcm(a, b)) mod b)`, which is a permutation of the product with both properties. The shuffle comes from shuffling the items of each axis, the order of the lcm long segments, and the order within each gcd long run, all of which keep the properties. The order of each axis repeats from one run to the next when the lengths are coprime, since then there's only one segment and the runs are one long, so it's best when they share factors.
+/// ```rust
+/// let sweep = Stratified::new(Cross(learning_rates, batch_sizes));
+/// ```
+#[derive(Clone)]
+pub struct Stratified<A, B> {
+    v: Cross<A, B>,
+    config: ShufflerConfig,
+}
+impl<A, B> Stratified<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    pub fn new(v: Cross<A, B>) -> Self {
+        Self::with_config(v, &ShufflerConfig::default())
+    }
+    /// shuffles with the algorithm, rounds and seed given in `config`
+    pub fn with_config(v: Cross<A, B>, config: &ShufflerConfig) -> Self {
+        Self { v, config: *config }
+    }
+    /// (gcd, lcm) of the axis lengths
+    fn periods(&self) -> (usize, usize) {
+        let (a, b) = (self.v.0.len(), self.v.1.len());
+        let g = gcd(a, b);
+        (g, a / g * b)
+    }
+    /// each part of the shuffle gets its own seed
+    fn shuffler(&self, salt: u64, len: usize) -> Seeded<DynShuffler> {
+        ShufflerConfig {
+            seed: rng::splitmix64(self.config.seed ^ rng::splitmix64(salt)),
+            ..self.config
+        }
+        .for_length(len)
+    }
+    fn run_shuffler(&self, run: usize, g: usize) -> Seeded<DynShuffler> {
+        self.shuffler(3 + run as u64, g)
+    }
+}
+impl<A, B> Indexing for Stratified<A, B>
+where
+    A: Indexing,
+    B: Indexing,
+{
+    type Item = (A::Item, B::Item);
+    fn len(&self) -> usize {
+        self.v.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        assert!(at < self.len());
+        let (a, b) = (self.v.0.len(), self.v.1.len());
+        let (g, l) = self.periods();
+        let k = rng::permute(&self.shuffler(2, g), g as u64, (at / l) as u64) as usize;
+        let r = rng::permute(&self.run_shuffler(at / g, g), g as u64, (at % g) as u64) as usize;
+        let p = at % l - at % g + r;
+        let (x, y) = (p % a, (p + k) % b);
+        (
+            self.v
+                .0
+                .get(rng::permute(&self.shuffler(0, a), a as u64, x as u64) as usize),
+            self.v
+                .1
+                .get(rng::permute(&self.shuffler(1, b), b as u64, y as u64) as usize),
+        )
+    }
+}
+impl<A, B> InverseIndexing for Stratified<A, B>
+where
+    A: InverseIndexing,
+    B: InverseIndexing,
+{
+    fn index_of(&self, (ia, ib): &Self::Item) -> Option<usize> {
+        let (a, b) = (self.v.0.len(), self.v.1.len());
+        let (g, l) = self.periods();
+        let x = rng::unpermute(
+            &self.shuffler(0, a),
+            a as u64,
+            self.v.0.index_of(ia)? as u64,
+        ) as usize;
+        let y = rng::unpermute(
+            &self.shuffler(1, b),
+            b as u64,
+            self.v.1.index_of(ib)? as u64,
+        ) as usize;
+        // the segment is whichever offset makes x and y line up mod g, then p is found by the chinese remainder theorem
+        let k = (y + g - x % g) % g;
+        let y0 = (y + b - k) % b;
+        let t = (y0 + b - x % b) % b / g * mod_inverse(a / g, b / g) % (b / g);
+        let p = x + a * t;
+        let segment = rng::unpermute(&self.shuffler(2, g), g as u64, k as u64) as usize;
+        let run = segment * (l / g) + p / g;
+        let r = rng::unpermute(&self.run_shuffler(run, g), g as u64, (p % g) as u64) as usize;
+        Some(run * g + r)
+    }
 }
-// pub fn heavy_shuffle()-> Shuffled<D, CipherShuffler>
 
 //todo: also lcgshuffle (very fast, better statistical properties than lfsr), symmetric cipher shuffle (slow but cryptographically random), pcrng shuffle (better statistical properties than either of the other fast ones)
 
@@ -385,8 +2360,10 @@ mod tests {
         let _c2m = c2.into_map(|(a, b): (usize, usize)| a + b);
     }
 
-    fn test_aperiodicity_for_length<S: Shuffler>(length: usize) -> bool {
-        let l = Rng::<S>::for_length(length);
+    fn test_aperiodicity_for_length<S: Shuffler + FromLength>(length: usize) -> bool {
+        test_aperiodicity(Rng::<S>::for_length(length), length)
+    }
+    fn test_aperiodicity<S: Shuffler>(l: Rng<S>, length: usize) -> bool {
         let mut s = std::collections::HashSet::new();
         //see that it's aperiodic at least until 3 steps away from the end
         for (i, e) in l.take(length).enumerate() {
@@ -423,36 +2400,437 @@ mod tests {
             panic!("oh no, we don't understand");
         }
     }
-    // deprecating
-    // #[test]
-    // fn triangle_second_numeral() {
-    //     const expected: &'static [(usize, usize)] = &[
-    //         (0, 0),
-    //         (1, 0),
-    //         (1, 1),
-    //         (2, 0),
-    //         (2, 1),
-    //         (2, 2),
-    //         (3, 0),
-    //         (3, 1),
-    //         (3, 2),
-    //         (3, 3),
-    //         (4, 0),
-    //         (4, 1),
-    //         (4, 2),
-    //         (4, 3),
-    //         (4, 4),
-    //         (5, 0),
-    //         (5, 1),
-    //         (5, 2),
-    //         (5, 3),
-    //         (5, 4),
-    //         (5, 5),
-    //     ];
-    //     for i in 1..expected.len() {
-    //         assert_eq!(triangle_1th(i), expected[i]);
-    //     }
-    // }
+    fn assert_permutation<I: Indexing<Item = usize>>(d: &I) {
+        let mut seen = vec![false; d.len()];
+        for e in d.iter() {
+            assert!(!seen[e], "{} was repeated", e);
+            seen[e] = true;
+        }
+        assert!(seen.iter().all(|s| *s));
+    }
+
+    #[test]
+    fn dyn_shuffler() {
+        use rng::{ShufflerKind, ShufflerConfig};
+        for kind in [ShufflerKind::LFSRF, ShufflerKind::LFSRFNTimes] {
+            for length in [1, 2, 7, 8, 100, 255] {
+                for seed in [0, 1, 9, 0xfeedbeef] {
+                    let config = ShufflerConfig { kind, rounds: 4, seed };
+                    assert_permutation(&Shuffled::with_config(0..length, &config));
+                    assert!(!test_aperiodicity(
+                        Rng::new(config.for_length(length), length),
+                        length
+                    ));
+                }
+            }
+        }
+        let seeded = |seed| -> Vec<usize> {
+            let config = ShufflerConfig {
+                seed,
+                ..ShufflerConfig::default()
+            };
+            Shuffled::with_config(0..50, &config).iter().collect()
+        };
+        assert_ne!(seeded(0), seeded(1));
+        assert_eq!(seeded(3), seeded(3));
+        let unseeded: Vec<usize> = light_shuffle(0..50).iter().collect();
+        assert_eq!(seeded(0), unseeded);
+        assert_eq!("lfsrf_n_times".parse(), Ok(ShufflerKind::LFSRFNTimes));
+        assert!("aes".parse::<ShufflerKind>().is_err());
+    }
+
+    #[test]
+    fn boxed_shuffler() {
+        let r: Box<dyn Shuffler> = Box::new(LFSRFNTimes::for_length(20));
+        let d = Shuffled::with_shuffler(0..20, r);
+        assert_permutation(&d);
+        let expected: Vec<usize> = light_shuffle(0..20).iter().collect();
+        assert_eq!(d.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn persist_roundtrip() {
+        use persist::{load, save, PersistError};
+        let d = light_shuffle(Cross(0..30, KSubsets::new(5, 2)));
+        let mut it = d.into_iter();
+        for _ in 0..17 {
+            it.next();
+        }
+        let saved = save(&it);
+        let restored: IndexingIter<Shuffled<Cross<Range<usize>, KSubsets>, DefaultShuffler>, _> =
+            load(&saved).unwrap();
+        assert_eq!(it.collect::<Vec<_>>(), restored.collect::<Vec<_>>());
+
+        let config = ShufflerConfig {
+            seed: 77,
+            ..ShufflerConfig::default()
+        };
+        let mut r = Rng::new(config.for_length(40), 40);
+        r.next();
+        let saved = save(&r);
+        let restored: Rng<Seeded<DynShuffler>> = load(&saved).unwrap();
+        assert_eq!(r.take(40).collect::<Vec<_>>(), restored.take(40).collect::<Vec<_>>());
+
+        let v = Truncate(2, IndexVec(vec!["a b".to_string(), "c\nd".into(), "".into()]));
+        let restored: Truncate<IndexVec<String>> = load(&save(&v)).unwrap();
+        assert_eq!(restored.iter().collect::<Vec<_>>(), ["a b", "c\nd"]);
+
+        // restoring with a different shuffler than the one that was saved
+        let saved = save(&Shuffled::<_, LFSRF>::new(0..10));
+        assert!(matches!(
+            load::<Shuffled<Range<usize>, LFSRFNTimes>>(&saved),
+            Err(PersistError::Tag { .. })
+        ));
+        let bumped = saved.replacen(" 1 ", " 999 ", 1);
+        assert!(matches!(
+            load::<Shuffled<Range<usize>, LFSRF>>(&bumped),
+            Err(PersistError::Version(999))
+        ));
+        assert!(load::<Shuffled<Range<usize>, LFSRF>>(&saved[..saved.len() - 2]).is_err());
+
+        // states, taps and cursors that don't fit are rejected rather than trusted
+        for bad in [
+            "mako_infinite_shuffle 1 Rng LFSRF 6 3 7 0",
+            "mako_infinite_shuffle 1 Rng LFSRF 3 3 7 0",
+            "mako_infinite_shuffle 1 Rng LFSRF 3 3 7 8",
+            "mako_infinite_shuffle 1 Rng LFSRF 3 3 6 7",
+            "mako_infinite_shuffle 1 Rng LFSRF 4 3 7 1",
+            "mako_infinite_shuffle 1 Rng LFSRF 13 3 7 1",
+        ] {
+            assert!(
+                matches!(load::<Rng<LFSRF>>(bad), Err(PersistError::Malformed(_))),
+                "{bad}"
+            );
+        }
+        assert!(load::<Rng<LFSRF>>("mako_infinite_shuffle 1 Rng LFSRF 3 3 7 7").is_ok());
+        let past = save(&Indexing::into_iter(0..5)).replacen(" 0 5 ", " 6 5 ", 1);
+        assert!(matches!(
+            load::<IndexingIter<Range<usize>, Range<usize>>>(&past),
+            Err(PersistError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn iter_random_access() {
+        let d = Cross(0..4, 0..5);
+        let mut it = d.iter();
+        assert_eq!(it.len(), 20);
+        assert_eq!(it.nth(6), Some((1, 1)));
+        assert_eq!(it.len(), 13);
+        assert_eq!(it.next_back(), Some((3, 4)));
+        assert_eq!(it.nth_back(1), Some((3, 2)));
+        assert_eq!(it.size_hint(), (10, Some(10)));
+        assert_eq!(it.nth(100), None);
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+        let rev: Vec<usize> = (10..15).iter().rev().collect();
+        assert_eq!(rev, [14, 13, 12, 11, 10]);
+        let stepped: Vec<usize> = (0..10).iter().step_by(3).collect();
+        assert_eq!(stepped, [0, 3, 6, 9]);
+        let skipped: Vec<usize> = (0..10).iter().skip(7).collect();
+        assert_eq!(skipped, [7, 8, 9]);
+        assert_eq!((0..10).iter().last(), Some(9));
+        assert_eq!((5..10).iter().count(), 5);
+        let o: Box<dyn Indexing<Item = usize>> = Box::new(0..3);
+        assert_eq!(dyn_iter(o).rev().collect::<Vec<_>>(), [2, 1, 0]);
+    }
+
+    #[test]
+    fn parallel() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let d = light_shuffle(Cross(0..37, 0..11));
+        let sequential: Vec<(usize, usize)> = d.iter().collect();
+        for threads in [1, 3, 8, 1000] {
+            let v = par::par_map_collect_with(&d, threads, |e| e);
+            assert_eq!(v, sequential);
+            let sum = AtomicUsize::new(0);
+            par::par_for_each_with(&d, threads, |(a, b)| {
+                sum.fetch_add(a * 11 + b, Ordering::Relaxed);
+            });
+            assert_eq!(sum.into_inner(), (0..37 * 11).sum());
+        }
+        let c: Vec<_> = par::chunks(10, 3).collect();
+        assert_eq!(c, [0..4, 4..7, 7..10]);
+        assert!(par::par_map_collect(&(0..0), |e| e).is_empty());
+    }
+
+    #[test]
+    fn shards() {
+        for mode in [ShardMode::Strided, ShardMode::Contiguous] {
+            for total in [0, 1, 7, 64, 101] {
+                let space = light_shuffle(0..total);
+                let mut seen = vec![false; total];
+                let mut lens = Vec::new();
+                for i in 0..8 {
+                    let s = Shard::with_mode(&space, i, 8, mode);
+                    lens.push(s.len());
+                    for e in s.iter() {
+                        assert!(!seen[e], "{} was repeated", e);
+                        seen[e] = true;
+                    }
+                }
+                assert!(seen.iter().all(|s| *s));
+                assert!(lens.iter().max().unwrap() - lens.iter().min().unwrap() <= 1);
+            }
+        }
+        let space = light_shuffle(Cross(0..100, 0..10));
+        let head = Truncate(space.len() / 10, &space);
+        let all: Vec<_> = (0..8)
+            .flat_map(|i| Shard::new(&head, i, 8).iter().collect::<Vec<_>>())
+            .collect();
+        assert_eq!(all.len(), 100);
+        assert!(all.iter().all(|e| head.iter().any(|h| h == *e)));
+    }
+
+    #[test]
+    fn atomic_cursor() {
+        use std::sync::Mutex;
+        let cursor = par::AtomicCursor::new(light_shuffle(Cross(0..50, 0..9)));
+        let seen = Mutex::new(Vec::new());
+        std::thread::scope(|s| {
+            for t in 0..6 {
+                let (cursor, seen) = (&cursor, &seen);
+                s.spawn(move || loop {
+                    let got: Vec<_> = if t % 2 == 0 {
+                        cursor.claim().into_iter().collect()
+                    } else {
+                        cursor.claim_batch(7).collect()
+                    };
+                    if got.is_empty() {
+                        break;
+                    }
+                    cursor.complete(got.len());
+                    seen.lock().unwrap().extend(got);
+                });
+            }
+        });
+        assert!(cursor.is_exhausted() && cursor.is_done());
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort();
+        let expected: Vec<_> = cursor.space().iter().enumerate().collect();
+        assert_eq!(seen, expected);
+        assert!(cursor.claim().is_none());
+        assert_eq!(cursor.claim_batch(3).len(), 0);
+
+        let resumed = par::AtomicCursor::starting_at(0..10, 8);
+        assert_eq!(resumed.claim_batch(5).collect::<Vec<_>>(), [(8, 8), (9, 9)]);
+        assert!(!resumed.is_done());
+    }
+
+    #[test]
+    fn progress() {
+        use progress::Progress;
+        let space = light_shuffle(Cross(0..20, 0..15));
+        let mut p = Progress::for_space(&space);
+        for i in (0..space.len()).filter(|i| i % 3 == 0 || (100..200).contains(i)) {
+            p.mark_done(i);
+        }
+        p.exclude(4);
+        p.exclude(7);
+        p.exclude(6);
+        p.mark_done(4);
+        assert_eq!(p.done_count(), 100 + 67 + 1);
+        assert_eq!(p.excluded_count(), 3);
+        assert_eq!(p.remaining_count(), 300 - 168 - 1);
+        let expected: Vec<_> = space
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !(i % 3 == 0 || (100..200).contains(i) || *i == 4 || *i == 7))
+            .collect();
+        assert_eq!(p.remaining(&space).collect::<Vec<_>>(), expected);
+
+        let path = std::env::temp_dir().join(format!("progress_test_{}", std::process::id()));
+        p.save_to_file(&path).unwrap();
+        let loaded = Progress::load_for_space(&path, &space).unwrap();
+        assert_eq!(loaded, p);
+        assert!(Progress::load_for_space(&path, &(0..10)).is_err());
+        // same length, different order
+        let reseeded = Shuffled::with_config(
+            Cross(0..20, 0..15),
+            &ShufflerConfig {
+                seed: 1,
+                ..ShufflerConfig::default()
+            },
+        );
+        assert!(Progress::load_for_space(&path, &reseeded).is_err());
+        Progress::new(space.len()).save_to_file(&path).unwrap();
+        assert!(Progress::load_for_space(&path, &space).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        let mut all = Progress::new(70);
+        for i in 0..70 {
+            all.mark_done(i);
+        }
+        assert!(all.is_finished());
+        assert_eq!(all.remaining_indices().count(), 0);
+    }
+
+    #[test]
+    fn distinct_and_union() {
+        let d = Distinct::new((0..20).into_map(|e| e % 7));
+        assert_eq!(d.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5, 6]);
+        let hand_written = IndexVec(vec!["a", "b", "b", "c"]);
+        let generated = (0..4).into_map(|i| ["c", "d", "a", "e"][i]);
+        let u = Union::new(hand_written, generated);
+        assert_eq!(u.len(), 5);
+        assert_eq!(u.iter().collect::<Vec<_>>(), ["a", "b", "c", "d", "e"]);
+        let many = UnionVec::new(vec![
+            IndexVec(vec!["a", "b", "b"]),
+            IndexVec(vec![]),
+            IndexVec(vec!["c", "a"]),
+            IndexVec(vec!["d", "c", "e"]),
+        ]);
+        assert_eq!(many.iter().collect::<Vec<_>>(), ["a", "b", "c", "d", "e"]);
+        let shuffled = light_shuffle(Union::new(u, IndexVec(vec!["f", "a"])));
+        let all: HashSet<_> = hashset_acc_without_repeat(shuffled.iter());
+        assert_eq!(all.len(), 6);
+    }
+
+    #[test]
+    fn cached() {
+        use cache::{CacheMode, Cached, SyncCached};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let calls = AtomicUsize::new(0);
+        let expensive = (0..10).into_map(|e| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            e * e
+        });
+        let c = Cached::new(&expensive, CacheMode::Full);
+        let s = Shuffled::<_, LFSRF>::new(Cross(&c, 0..3));
+        let first: Vec<_> = s.iter().collect();
+        assert_eq!(s.iter().collect::<Vec<_>>(), first);
+        assert_eq!(calls.load(Ordering::Relaxed), 10);
+
+        calls.store(0, Ordering::Relaxed);
+        let c = Cached::new(&expensive, CacheMode::Lru(2));
+        for i in [1, 2, 1, 3, 1, 2] {
+            assert_eq!(c.get(i), i * i);
+        }
+        // 2 was evicted by 3, 1 never was
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+
+        calls.store(0, Ordering::Relaxed);
+        let c = SyncCached::new(&expensive, CacheMode::Lru(4));
+        let v = par::par_map_collect(&Cross(&c, 0..50), |(a, _)| a);
+        assert_eq!(v.len(), 500);
+        assert!(calls.load(Ordering::Relaxed) < 500);
+
+        let m = light_shuffle(KSubsets::new(5, 2)).materialize();
+        assert_eq!(m.0, light_shuffle(KSubsets::new(5, 2)).iter().collect::<Vec<_>>());
+    }
+
+    fn assert_inverse<I>(d: &I)
+    where
+        I: InverseIndexing,
+        I::Item: Debug,
+    {
+        for i in 0..d.len() {
+            assert_eq!(d.index_of(&d.get(i)), Some(i), "{:?}", d.get(i));
+        }
+    }
+
+    #[test]
+    fn zip_enumerate_optional() {
+        let z = Zip(10..20, IndexVec(vec!['a', 'b', 'c']));
+        assert_eq!(z.iter().collect::<Vec<_>>(), [(10, 'a'), (11, 'b'), (12, 'c')]);
+        assert_inverse(&z);
+        assert_eq!(z.index_of(&(11, 'c')), None);
+        assert_eq!(z.index_of(&(15, 'a')), None);
+        let e = Enumerate(light_shuffle(0..6));
+        let firsts: Vec<usize> = e.iter().map(|(i, _)| i).collect();
+        assert_eq!(firsts, [0, 1, 2, 3, 4, 5]);
+        let o = Optional(5..7);
+        assert_eq!(o.iter().collect::<Vec<_>>(), [None, Some(5), Some(6)]);
+        assert_inverse(&o);
+        assert_inverse(&Cross(Optional(0..3), Zip(KSubsets::new(5, 2), 0..4)));
+        assert_inverse(&Cross(Once(1), KSubmultisets::new(4, 3)));
+        assert_inverse(&Series(Truncate(3, 2..9), IndexVec(vec!['x', 'y'])));
+        assert_eq!(Series(0..2, 5..7).iter().collect::<Vec<_>>(), [Ok(0), Ok(1), Err(5), Err(6)]);
+        assert_eq!(KSubsets::new(4, 2).index_of(&vec![1, 4]), None);
+        assert_eq!(KSubmultisets::new(4, 2).index_of(&vec![2, 1]), None);
+        for mode in [ShardMode::Strided, ShardMode::Contiguous] {
+            for i in 0..3 {
+                let s = Shard::with_mode(0..10, i, 3, mode);
+                assert_inverse(&s);
+                assert_eq!((0..10).filter(|e| s.index_of(e).is_some()).count(), s.len());
+            }
+        }
+    }
+
+    #[test]
+    fn views() {
+        let space = light_shuffle(0..1000);
+        let second_hundred: Vec<usize> = space.window(100..200).iter().collect();
+        assert_eq!(second_hundred, space.iter().skip(100).take(100).collect::<Vec<_>>());
+        assert_eq!(space.window(990..2000).len(), 10);
+        assert_eq!(space.window(2000..3000).len(), 0);
+        let d = 0..10;
+        assert_eq!(d.skipped(7).iter().collect::<Vec<_>>(), [7, 8, 9]);
+        assert_eq!(d.skipped(70).len(), 0);
+        assert_eq!(d.stepped_by(4).iter().collect::<Vec<_>>(), [0, 4, 8]);
+        assert_eq!(d.truncated(2).iter().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(d.reversed().window(0..3).iter().collect::<Vec<_>>(), [9, 8, 7]);
+        assert_eq!((0..2).repeated(3).iter().collect::<Vec<_>>(), [0, 1, 0, 1, 0, 1]);
+        assert_inverse(&Window(3..7, 0..10));
+        assert_inverse(&Skip(3, 0..10));
+        assert_inverse(&StepBy(3, 0..10));
+        assert_inverse(&Rev(0..10));
+        assert_inverse(&Repeat(1, 0..10));
+        assert_eq!(Repeat(3, 0..10).index_of(&4), Some(4));
+        assert_eq!(StepBy(3, 0..10).index_of(&4), None);
+        assert_eq!(Window(3..7, 0..10).index_of(&8), None);
+    }
+
+    #[test]
+    fn triangle_second_numeral() {
+        const EXPECTED: &[(usize, usize)] = &[
+            (0, 0),
+            (1, 0),
+            (1, 1),
+            (2, 0),
+            (2, 1),
+            (2, 2),
+            (3, 0),
+            (3, 1),
+            (3, 2),
+            (3, 3),
+            (4, 0),
+            (4, 1),
+            (4, 2),
+            (4, 3),
+            (4, 4),
+            (5, 0),
+            (5, 1),
+            (5, 2),
+            (5, 3),
+            (5, 4),
+            (5, 5),
+        ];
+        let t = Triangle(6);
+        assert_eq!(t.iter().collect::<Vec<_>>(), EXPECTED);
+        assert_inverse(&t);
+        assert_eq!(t.index_of(&(2, 3)), None);
+        let dependent = DependentCross::new(0..6, |i: &usize| 0..*i + 1);
+        assert_eq!(dependent.iter().collect::<Vec<_>>(), EXPECTED);
+        let big = Triangle(1 << 20);
+        assert_eq!(big.get(big.len() - 1), ((1 << 20) - 1, (1 << 20) - 1));
+        assert_inverse(&Window(big.len() - 100..big.len(), &big));
+    }
+
+    #[test]
+    fn dependent_cross() {
+        // choose a level, then one of the layouts valid for it. Level 2 has none.
+        let layouts = [3, 1, 0, 4];
+        let d = DependentCross::new(0..4, |l: &usize| KSubsets::new(layouts[*l], 1));
+        assert_eq!(d.len(), 8);
+        let v: Vec<_> = d.iter().map(|(l, k)| (l, k[0])).collect();
+        assert_eq!(v, [(0, 0), (0, 1), (0, 2), (1, 0), (3, 0), (3, 1), (3, 2), (3, 3)]);
+        assert_inverse(&d);
+        assert_eq!(d.index_of(&(2, vec![0])), None);
+        let s = light_shuffle(&d);
+        let all = hashset_acc_without_repeat(s.iter());
+        assert_eq!(all.len(), 8);
+    }
 
     #[test]
     fn ksubsetsmulti() {
@@ -505,7 +2883,6 @@ mod tests {
         assert_eq!(&ac, &cc);
     }
 
-    use std::collections::HashSet;
     fn hashset_acc_without_repeat<T: Hash + Eq + Debug>(
         all: impl Iterator<Item = T>,
     ) -> HashSet<T> {
@@ -537,7 +2914,7 @@ mod tests {
                 .take(40),
         );
         println!("{:?}", &rs);
-        assert!(rs.iter().any(|v| v.iter().any(|e| *e == 7)), "no 7s. the shuffler was insufficiently random.");
+        assert!(rs.iter().any(|v| v.contains(&7)), "no 7s. the shuffler was insufficiently random.");
         assert!(rs.iter().any(|v| *v.iter().next().unwrap() == 7), "no 7s. the shuffler was insufficiently random.");
     }
 
@@ -562,4 +2939,591 @@ mod tests {
         let ti:Vec<usize> = Truncate(3, 10..30).iter().collect();
         assert_eq!(&[10,11,12], &*ti);
     }
+
+    #[test]
+    fn infinite() {
+        use infinite::{pair, unpair, FiniteCross, InfiniteIndexing, Naturals, Prefix};
+        let squares: HashSet<_> = Prefix(100, infinite::Cross(Naturals, Naturals)).iter().collect();
+        let expected: HashSet<_> = Cross(0..10, 0..10)
+            .iter()
+            .map(|(a, b)| (a as u64, b as u64))
+            .collect();
+        assert_eq!(squares, expected);
+        for z in [0, 1, 2, 99, 1 << 40, u32::MAX as u64 * u32::MAX as u64] {
+            let (x, y) = unpair(z);
+            assert_eq!(pair(x, y), z);
+        }
+        let fc = FiniteCross(IndexVec(vec!['a', 'b', 'c']), Naturals);
+        assert_eq!(fc.iter().nth(7), Some(('b', 2)));
+
+        let s = infinite::Shuffled::new(Naturals);
+        let head: Vec<usize> = s.iter().take(1 << 12).map(|e| e as usize).collect();
+        assert_permutation(&IndexVec(head));
+        // far out, where blocks have stopped doubling
+        let far = (1u64 << 40) + 12345;
+        // 2^40 is a multiple of the block length past 2^31, so a block starts there
+        let block_start = 1u64 << 40;
+        let outs: HashSet<u64> = (far..far + 1000).map(|at| s.get(at)).collect();
+        assert_eq!(outs.len(), 1000);
+        assert!(outs.iter().all(|o| (block_start..block_start + (1 << 30)).contains(o)));
+        // blocks of the same length are shuffled differently, as are different seeds
+        let offsets = |s: &infinite::Shuffled<Naturals>, start: u64| -> Vec<u64> {
+            (start..start + 16).map(|at| s.get(at) - start).collect()
+        };
+        assert_ne!(
+            offsets(&s, block_start),
+            offsets(&s, block_start + (1 << 30))
+        );
+        let reseeded = infinite::Shuffled::with_config(
+            Naturals,
+            &ShufflerConfig {
+                seed: 3,
+                ..ShufflerConfig::default()
+            },
+        );
+        assert_ne!(offsets(&s, 1 << 12), offsets(&reseeded, 1 << 12));
+    }
+
+    #[test]
+    fn growing_shuffle() {
+        use growing::GrowingShuffle;
+        let mut g = GrowingShuffle::<DefaultShuffler>::new(50);
+        let mut visited: Vec<usize> = (0..20).map(|_| g.next_index().unwrap()).collect();
+        let before_growth: Vec<usize> = (0..20).map(|i| g.get(i)).collect();
+        g.grow_to(80);
+        g.grow_to(80);
+        assert_eq!((0..20).map(|i| g.get(i)).collect::<Vec<_>>(), before_growth);
+        visited.extend((0..30).map(|_| g.next_index().unwrap()));
+        // growing again before anything more is visited widens the current epoch
+        g.grow_to(90);
+        g.grow_to(100);
+        assert_eq!(g.epoch_count(), 3);
+        let saved = persist::save(&g);
+        visited.extend((0..20).map(|_| g.next_index().unwrap()));
+        let mut restored: GrowingShuffle<DefaultShuffler> = persist::load(&saved).unwrap();
+        for _ in 0..20 {
+            restored.next_index();
+        }
+        g.grow_to(101);
+        restored.grow_to(101);
+        while let Some(i) = g.next_index() {
+            assert_eq!(restored.next_index(), Some(i));
+            visited.push(i);
+        }
+        assert_eq!(restored.next_index(), None);
+        assert_permutation(&IndexVec(visited));
+        assert_permutation(&g);
+    }
+
+    #[test]
+    fn inverse_shuffle() {
+        use rng::InvertibleShuffler;
+        for size in 2..=32u32 {
+            let l = LFSRF::for_length((1usize << (size - 1)) - 1);
+            assert_eq!(l.size, size);
+            for state in [1, 2, 3, (1 << size) - 1, 0x5a5a5a5a & ((1 << size) - 1)] {
+                if state > 0 {
+                    assert_eq!(l.prev(l.next(state)), state);
+                }
+            }
+        }
+        assert_inverse(&light_shuffle(Cross(0..13, KSubsets::new(6, 3))));
+        let config = ShufflerConfig {
+            seed: 5,
+            ..ShufflerConfig::default()
+        };
+        assert_inverse(&Shuffled::with_config(Permutations::new(4), &config));
+        let p = Permutations::new(3);
+        assert_eq!(
+            p.iter().collect::<Vec<_>>(),
+            [
+                [0, 1, 2],
+                [0, 2, 1],
+                [1, 0, 2],
+                [1, 2, 0],
+                [2, 0, 1],
+                [2, 1, 0]
+            ]
+        );
+        assert_eq!(p.index_of(&vec![0, 0, 1]), None);
+        let c = CrossVec(vec![0..2, 0..3, 5..7]);
+        assert_eq!(c.len(), 12);
+        assert_eq!(c.get(7), [1, 0, 6]);
+        assert_inverse(&c);
+        assert_eq!(
+            CrossVec::<Range<usize>>(vec![]).iter().collect::<Vec<_>>(),
+            [Vec::<usize>::new()]
+        );
+    }
+
+    #[test]
+    fn interleave() {
+        let i = Interleave(0..3, 10..15);
+        assert_eq!(
+            i.iter().collect::<Vec<_>>(),
+            [
+                Ok(0),
+                Err(10),
+                Ok(1),
+                Err(11),
+                Ok(2),
+                Err(12),
+                Err(13),
+                Err(14)
+            ]
+        );
+        assert_inverse(&i);
+        assert_inverse(&Interleave(0..4, 0..1));
+
+        let v = InterleaveVec::new(vec![0..3, 10..11, 0..0, 20..22]);
+        assert_eq!(
+            v.iter().collect::<Vec<_>>(),
+            [(0, 0), (1, 10), (3, 20), (0, 1), (3, 21), (0, 2)]
+        );
+        assert_inverse(&v);
+        assert_eq!(InterleaveVec::<Range<usize>>::new(vec![]).len(), 0);
+
+        let p = ProportionalInterleave::new(vec![0..2, 0..6]);
+        assert_eq!(
+            p.iter().collect::<Vec<_>>(),
+            [
+                (1, 0),
+                (0, 0),
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (0, 1),
+                (1, 4),
+                (1, 5)
+            ]
+        );
+        let p = ProportionalInterleave::new(vec![0..7, 0..0, 0..100, 0..1, 0..33]);
+        assert_inverse(&p);
+        let firsts: Vec<usize> = p
+            .iter()
+            .take(28)
+            .filter(|(s, _)| *s == 2)
+            .map(|(_, i)| i)
+            .collect();
+        assert_eq!(firsts, (0..firsts.len()).collect::<Vec<_>>());
+        assert!((15..=20).contains(&firsts.len()));
+        // against sorting every key outright
+        let lens = [5, 12, 1, 12, 30, 7];
+        let mut keys: Vec<(usize, usize)> = (0..lens.len())
+            .flat_map(|s| (0..lens[s]).map(move |j| (s, j)))
+            .collect();
+        keys.sort_by(|(s, j), (t, i)| {
+            ((2 * j + 1) * lens[*t])
+                .cmp(&((2 * i + 1) * lens[*s]))
+                .then(s.cmp(t))
+        });
+        let p = ProportionalInterleave::new(lens.iter().map(|l| 0..*l).collect());
+        assert_eq!(p.iter().collect::<Vec<_>>(), keys);
+        // lengths don't slow it down
+        let lens = [3, 1 << 40, 5, (1 << 20) + 7, 1 << 33];
+        let total: usize = lens.iter().sum();
+        for at in [0, 1, 2, total / 3, total / 2 + 11, total - 2, total - 1] {
+            let (s, j) = proportional_unrank(&lens, total, at);
+            assert_eq!(proportional_rank(&lens, s, j), at);
+        }
+    }
+
+    #[test]
+    fn diagonal_orders() {
+        let d = DiagonalCross(0..3, 0..2);
+        assert_eq!(
+            d.iter().collect::<Vec<_>>(),
+            [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]
+        );
+        assert_inverse(&d);
+        assert_inverse(&DiagonalCross(0..2, 0..7));
+        assert_inverse(&DiagonalCross(0..1, 0..1));
+        assert_eq!(DiagonalCross(0..0, 0..5).len(), 0);
+
+        let s = ShellCross(0..3, 0..2);
+        assert_eq!(
+            s.iter().collect::<Vec<_>>(),
+            [(0, 0), (1, 0), (1, 1), (0, 1), (2, 0), (2, 1)]
+        );
+        assert_inverse(&s);
+        assert_inverse(&ShellCross(0..2, 0..6));
+        let square: HashSet<(usize, usize)> = ShellCross(0..10, 0..10).iter().take(16).collect();
+        assert_eq!(square, Cross(0..4, 0..4).iter().collect());
+
+        let l = LevelCross::new(vec![0..2, 0..3, 0..2]);
+        assert_eq!(l.len(), 12);
+        let sums: Vec<usize> = l.iter().map(|v| v.iter().sum()).collect();
+        assert_eq!(sums, [0, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 4]);
+        assert_eq!(l.get(1), [0, 0, 1]);
+        assert_inverse(&l);
+        assert_eq!(LevelCross::new(vec![0..2, 0..0]).len(), 0);
+        assert_eq!(LevelCross::<Range<usize>>::new(vec![]).len(), 1);
+    }
+
+    #[test]
+    fn gray_orders() {
+        fn differences<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+            a.iter().zip(b.iter()).filter(|(x, y)| x != y).count()
+        }
+        let g = GrayCross(0..3, 0..4);
+        let items: Vec<(usize, usize)> = g.iter().collect();
+        assert_eq!(
+            &items[..6],
+            [(0, 0), (0, 1), (0, 2), (0, 3), (1, 3), (1, 2)]
+        );
+        for w in items.windows(2) {
+            assert_eq!(w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1), 1);
+        }
+        assert_inverse(&g);
+
+        let g = GrayCrossVec(vec![0..3, 0..2, 0..4]);
+        let items: Vec<Vec<usize>> = g.iter().collect();
+        for w in items.windows(2) {
+            assert_eq!(differences(&w[0], &w[1]), 1);
+            let moved: usize = w[0]
+                .iter()
+                .zip(w[1].iter())
+                .map(|(x, y)| x.abs_diff(*y))
+                .sum();
+            assert_eq!(moved, 1);
+        }
+        assert_eq!(items.iter().collect::<HashSet<_>>().len(), 24);
+        assert_inverse(&g);
+
+        let r = RevolvingDoorSubsets::new(7, 3);
+        let items: Vec<Vec<usize>> = r.iter().collect();
+        assert_eq!(&items[..3], [[0, 1, 2], [0, 2, 3], [1, 2, 3]]);
+        for w in items.windows(2) {
+            let a: HashSet<&usize> = w[0].iter().collect();
+            assert_eq!(w[1].iter().filter(|e| !a.contains(e)).count(), 1);
+        }
+        assert_eq!(
+            items.iter().collect::<HashSet<_>>(),
+            KSubsets::new(7, 3)
+                .iter()
+                .collect::<Vec<_>>()
+                .iter()
+                .collect()
+        );
+        assert_inverse(&r);
+        assert_inverse(&RevolvingDoorSubsets::new(6, 0));
+        assert_inverse(&RevolvingDoorSubsets::new(6, 6));
+    }
+
+    #[test]
+    fn low_discrepancy() {
+        let l = LowDiscrepancy::new(0..8);
+        assert_eq!(l.iter().collect::<Vec<_>>(), [0, 4, 2, 6, 1, 5, 3, 7]);
+        assert_eq!(
+            LowDiscrepancy::new(0..5).iter().collect::<Vec<_>>(),
+            [0, 4, 2, 1, 3]
+        );
+        for n in [0, 1, 2, 7, 100] {
+            assert_permutation(&LowDiscrepancy::new(0..n));
+            assert_permutation(&LowDiscrepancy::scrambled(0..n, 3));
+        }
+        let g = LowDiscrepancy::new(Cross(0..8, 0..8));
+        let quadrants: HashSet<(usize, usize)> =
+            g.iter().take(4).map(|(y, x)| (y / 4, x / 4)).collect();
+        assert_eq!(quadrants.len(), 4);
+        let s = LowDiscrepancy::scrambled(Cross(0..8, 0..16), 11);
+        let quadrants: HashSet<(usize, usize)> =
+            s.iter().take(4).map(|(y, x)| (y / 4, x / 8)).collect();
+        assert_eq!(quadrants.len(), 4);
+        let sixteenths: HashSet<(usize, usize)> =
+            s.iter().take(16).map(|(y, x)| (y / 2, x / 4)).collect();
+        assert_eq!(sixteenths.len(), 16);
+        assert_inverse(&s);
+        assert_inverse(&LowDiscrepancy::scrambled(Cross(0..6, 0..10), 11));
+        assert_inverse(&LowDiscrepancy::new(Cross(0..3, 0..1)));
+        let v = LowDiscrepancy::scrambled(CrossVec(vec![0..3, 0..5, 0..2]), 1);
+        assert_eq!(v.iter().collect::<HashSet<_>>().len(), 30);
+        assert_inverse(&v);
+        assert_ne!(
+            LowDiscrepancy::scrambled(0..64, 1)
+                .iter()
+                .collect::<Vec<_>>(),
+            LowDiscrepancy::scrambled(0..64, 2)
+                .iter()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stratified() {
+        for (a, b) in [(4, 6), (5, 5), (3, 5), (1, 7), (6, 2)] {
+            let s = Stratified::new(Cross(0..a, 0..b));
+            let items: Vec<(usize, usize)> = s.iter().collect();
+            assert_eq!(items.iter().collect::<HashSet<_>>().len(), a * b);
+            for run in items.chunks(a) {
+                assert_eq!(run.iter().map(|(x, _)| *x).collect::<HashSet<_>>().len(), a);
+            }
+            for run in items.chunks(b) {
+                assert_eq!(run.iter().map(|(_, y)| *y).collect::<HashSet<_>>().len(), b);
+            }
+            assert_inverse(&s);
+        }
+        let config = ShufflerConfig {
+            seed: 9,
+            ..ShufflerConfig::default()
+        };
+        let seeded = Stratified::with_config(Cross(0..6, 0..6), &config);
+        assert_inverse(&seeded);
+        assert_ne!(
+            seeded.iter().collect::<Vec<_>>(),
+            Stratified::new(Cross(0..6, 0..6))
+                .iter()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn continuous_axes() {
+        use continuous::{DyadicRefinement, Linspace, Logspace};
+        use infinite::{InfiniteIndexing, Prefix};
+        let l = Linspace(0.0, 1.0, 5);
+        assert_eq!(l.iter().collect::<Vec<f64>>(), [0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(Linspace(2.0f32, 3.0, 1).iter().collect::<Vec<_>>(), [2.0]);
+        assert_eq!(Linspace(0.1, 0.7, 7).get(6), 0.7);
+        assert_inverse(&Linspace(-3.0, 7.1, 101));
+        assert_inverse(&Linspace(1.0f32, -1.0, 33));
+        assert_eq!(Linspace(1.0, 1.0, 3).index_of(&1.0), Some(0));
+        assert_eq!(Linspace(1.0, 1.0, 3).index_of(&2.0), None);
+        assert_eq!(Logspace(5.0, 5.0, 4).index_of(&5.0), Some(0));
+        assert_eq!(l.index_of(&0.3), None);
+
+        let g = Logspace(1e-5f64, 1e-1, 5);
+        for (x, e) in g.iter().zip([1e-5, 1e-4, 1e-3, 1e-2, 1e-1]) {
+            assert!((x / e - 1.0).abs() < 1e-12);
+        }
+        assert_eq!(g.get(4), 1e-1);
+        assert_inverse(&g);
+        assert_inverse(&Logspace(-2.0f32, -512.0, 10));
+
+        let d = DyadicRefinement(0.0, 8.0);
+        assert_eq!(
+            d.iter().take(9).collect::<Vec<f64>>(),
+            [0.0, 8.0, 4.0, 2.0, 6.0, 1.0, 3.0, 5.0, 7.0]
+        );
+        let mut grid: Vec<f32> = Prefix(17, DyadicRefinement(-1.0f32, 1.0)).iter().collect();
+        grid.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(grid, Linspace(-1.0f32, 1.0, 17).iter().collect::<Vec<_>>());
+
+        let sweep = light_shuffle(Cross(Logspace(1e-5f64, 1e-1, 9), Linspace(0.0f64, 0.9, 10)));
+        assert_eq!(
+            sweep
+                .iter()
+                .map(|(a, b)| (a.to_bits(), b.to_bits()))
+                .collect::<HashSet<_>>()
+                .len(),
+            90
+        );
+        assert_inverse(&sweep);
+    }
+
+    #[test]
+    fn weighted() {
+        use infinite::InfiniteIndexing;
+        use weighted::{Weighted, WeightedPermutation};
+        let weights = [1, 1, 2, 2, 4, 0];
+        let w = Weighted::new(0..6, |i| weights[*i]);
+        assert_eq!(w.period(), 10);
+        assert_eq!(w.class_count(), 3);
+        let out: Vec<usize> = w.iter().take(30).collect();
+        for period in out.chunks(10) {
+            for (i, weight) in weights.iter().enumerate() {
+                assert_eq!(period.iter().filter(|e| **e == i).count() as u64, *weight);
+            }
+        }
+        let fours: Vec<usize> = (0..30).filter(|p| out[*p] == 4).collect();
+        assert!(fours.windows(2).all(|g| g[1] - g[0] <= 4), "{fours:?}");
+
+        let q = Weighted::quantized(IndexVec(vec![0.001, 1.0, 0.5, 0.0]), |w| *w, 100);
+        assert_eq!(q.period(), 1 + 100 + 50);
+        let out: Vec<f64> = q.iter().take(151).collect();
+        assert_eq!(out.iter().filter(|w| **w == 0.001).count(), 1);
+        assert_eq!(out.iter().filter(|w| **w == 0.0).count(), 0);
+
+        let config = ShufflerConfig {
+            seed: 3,
+            ..ShufflerConfig::default()
+        };
+        let seeded = Weighted::new(0..50, |i| 1 + (*i as u64 % 3)).with_config(&config);
+        let first: HashSet<usize> = seeded.iter().take(seeded.period()).collect();
+        assert_eq!(first.len(), 50);
+
+        let p = WeightedPermutation::new(0..10, |i| if *i == 7 { 100.0 } else { 1.0 }, 1);
+        assert_permutation(&p);
+        assert_inverse(&p);
+        let firsts = (0..200)
+            .filter(|seed| {
+                WeightedPermutation::new(0..10, |i| if *i == 7 { 100.0 } else { 1.0 }, *seed).get(0)
+                    == 7
+            })
+            .count();
+        assert!(firsts > 150, "{firsts}");
+        let z = WeightedPermutation::new(0..4, |i| if *i == 0 { 0.0 } else { 1.0 }, 5);
+        assert_eq!(z.get(3), 0);
+    }
+
+    #[test]
+    fn sampling() {
+        use sample::{sample, sample_distinct, samples};
+        let mut state = 7;
+        let mut counts = [0; 10];
+        for _ in 0..10000 {
+            counts[sample(&(0..10), &mut state)] += 1;
+        }
+        assert!(counts.iter().all(|c| (800..1200).contains(c)), "{counts:?}");
+        let mut again = 7;
+        assert_eq!(
+            sample(&(0..1000), &mut again),
+            samples(&(0..1000), 7).next().unwrap()
+        );
+        let dynamic: &dyn Indexing<Item = (usize, usize)> = &Cross(0..5, 0..5);
+        assert_eq!(samples(dynamic, 1).take(100).count(), 100);
+
+        for (len, k) in [(1000, 3), (1000, 62), (100, 99), (20, 50), (5, 0)] {
+            let s = sample_distinct(&(0..len), k, 11);
+            assert_eq!(s.len(), k.min(len));
+            assert!(s.iter().all(|e| *e < len));
+            assert_eq!(s.iter().collect::<HashSet<_>>().len(), s.len());
+            assert_eq!(s, sample_distinct(&(0..len), k, 11));
+        }
+        assert_ne!(
+            sample_distinct(&(0..1000), 5, 1),
+            sample_distinct(&(0..1000), 5, 2)
+        );
+        let mut hits = [0; 10];
+        for seed in 0..5000 {
+            for e in sample_distinct(&(0..1000), 3, seed) {
+                hits[e / 100] += 1;
+            }
+        }
+        assert!(hits.iter().all(|h| (1300..1700).contains(h)), "{hits:?}");
+    }
+
+    #[test]
+    fn get_into() {
+        let s = light_shuffle(KSubsets::new(9, 4));
+        let mut buf = [0; 4];
+        for i in 0..s.len() {
+            s.get_into(i, &mut buf);
+            assert_eq!(buf[..], s.get(i)[..]);
+        }
+        let m = KSubmultisets::new(5, 3);
+        let by_ref = light_shuffle(&m);
+        let mut buf = vec![0; 3];
+        for i in 0..m.len() {
+            by_ref.get_into(i, &mut buf);
+            assert_eq!(buf, by_ref.get(i));
+        }
+        let p = Permutations::new(4);
+        let mut buf = [9; 4];
+        for i in 0..p.len() {
+            p.get_into(i, &mut buf);
+            assert_eq!(buf[..], p.get(i)[..]);
+        }
+
+        let n = KSubsetsN::<3>::new(8);
+        assert_eq!(n.len(), KSubsets::new(8, 3).len());
+        assert!(n
+            .iter()
+            .zip(KSubsets::new(8, 3).iter())
+            .all(|(a, b)| a[..] == b[..]));
+        assert_inverse(&n);
+        assert_eq!(n.index_of(&[2, 1, 5]), None);
+        let n = KSubmultisetsN::<3>::new(4);
+        assert!(n
+            .iter()
+            .zip(KSubmultisets::new(4, 3).iter())
+            .all(|(a, b)| a[..] == b[..]));
+        assert_inverse(&n);
+        assert_eq!(KSubsetsN::<0>::new(5).iter().collect::<Vec<_>>(), [[]]);
+    }
+
+    #[test]
+    fn successors() {
+        use std::cell::Cell;
+        fn same_order<I>(d: &I)
+        where
+            I: Successor,
+            I::Item: Clone + PartialEq + Debug,
+        {
+            let by_get: Vec<I::Item> = (0..d.len()).map(|i| d.get(i)).collect();
+            assert_eq!(d.iter_successive().collect::<Vec<_>>(), by_get);
+            // mixing directions and skips
+            let mut it = d.iter_successive();
+            let mut front = 0;
+            let mut back = d.len();
+            for step in 0.. {
+                let (got, expected) = match step % 4 {
+                    0 | 1 => (it.next(), (front < back).then_some(front)),
+                    2 => (it.next_back(), (front < back).then_some(back - 1)),
+                    _ => (it.nth(2), (front + 2 < back).then_some(front + 2)),
+                };
+                match expected {
+                    Some(e) => {
+                        assert_eq!(got.as_ref(), Some(&by_get[e]));
+                        if step % 4 == 2 {
+                            back = e;
+                        } else {
+                            front = e + 1;
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+        same_order(&KSubsets::new(9, 4));
+        same_order(&KSubsets::new(5, 5));
+        same_order(&KSubsets::new(4, 0));
+        same_order(&KSubmultisets::new(4, 3));
+        same_order(&KSubmultisetsN::<3>::new(4));
+        same_order(&KSubsetsN::<3>::new(10));
+        same_order(&Cross(KSubsets::new(6, 2), 0..3));
+        same_order(&Cross(0..4, Cross(KSubsetsN::<2>::new(5), 0..2)));
+        same_order(&Cross(0..1, 0..1));
+        same_order(&CrossVec(vec![0..3, 5..6, 2..4]));
+        let boxed: Box<dyn Successor<Item = Vec<usize>>> = Box::new(KSubsets::new(7, 3));
+        same_order(&boxed);
+
+        // only plain forward steps advance, and only when the next item is asked for
+        struct Counted(KSubsets, Cell<usize>, Cell<usize>);
+        impl Indexing for Counted {
+            type Item = Vec<usize>;
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+            fn get(&self, at: usize) -> Vec<usize> {
+                self.1.set(self.1.get() + 1);
+                self.0.get(at)
+            }
+        }
+        impl Successor for Counted {
+            fn advance(&self, at: usize, item: &mut Vec<usize>) {
+                self.2.set(self.2.get() + 1);
+                self.0.advance(at, item)
+            }
+        }
+        let c = Counted(KSubsets::new(10, 3), Cell::new(0), Cell::new(0));
+        let counts = |c: &Counted| (c.1.replace(0), c.2.replace(0));
+        let d = Cross(&c, 0..10);
+        d.iter_successive().next();
+        assert_eq!(counts(&c), (1, 0));
+        assert_eq!(d.iter_successive().step_by(10).count(), 120);
+        assert_eq!(counts(&c), (120, 0));
+        assert_eq!(d.iter_successive().count(), 1200);
+        assert_eq!(counts(&c), (0, 0));
+        d.iter_successive().for_each(drop);
+        assert_eq!(counts(&c), (1, 119));
+
+        let mut c = vec![0, 1, 3];
+        assert!(next_combination(&mut c));
+        assert_eq!(c, [0, 2, 3]);
+        assert!(next_combination(&mut c));
+        assert_eq!(c, [1, 2, 3]);
+        assert!(next_combination(&mut c));
+        assert_eq!(c, [0, 1, 4]);
+    }
 }
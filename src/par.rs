@@ -0,0 +1,165 @@
+//! Parallel iteration. `Indexing::get` is random access, so a space splits across threads by index range with no coordination at all, or, when work items take uneven amounts of time, can be handed out one at a time from an `AtomicCursor` with nothing more than an atomic increment.
+
+use std::{
+    num::NonZeroUsize,
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::Indexing;
+
+/// how many threads the `par_` functions use when not told otherwise
+pub fn default_threads() -> usize {
+    thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+}
+
+/// splits `0..len` into at most `parts` contiguous ranges whose lengths differ by at most one, in order. Only `len == 0` produces an empty range.
+pub fn chunks(len: usize, parts: usize) -> impl Iterator<Item = Range<usize>> {
+    let parts = parts.min(len).max(1);
+    let (q, r) = (len / parts, len % parts);
+    (0..parts).map(move |i| {
+        let start = i * q + i.min(r);
+        start..start + q + usize::from(i < r)
+    })
+}
+
+/// calls `f` on every item of `space` from `threads` threads. Items are visited in no particular order.
+pub fn par_for_each_with<I, F>(space: &I, threads: usize, f: F)
+where
+    I: Indexing + Sync + ?Sized,
+    F: Fn(I::Item) + Sync,
+{
+    let f = &f;
+    thread::scope(|s| {
+        for c in chunks(space.len(), threads) {
+            s.spawn(move || {
+                for i in c {
+                    f(space.get(i));
+                }
+            });
+        }
+    });
+}
+
+pub fn par_for_each<I, F>(space: &I, f: F)
+where
+    I: Indexing + Sync + ?Sized,
+    F: Fn(I::Item) + Sync,
+{
+    par_for_each_with(space, default_threads(), f)
+}
+
+/// maps every item of `space` through `f` from `threads` threads, and collects the results in the order of the space
+pub fn par_map_collect_with<I, F, R>(space: &I, threads: usize, f: F) -> Vec<R>
+where
+    I: Indexing + Sync + ?Sized,
+    F: Fn(I::Item) -> R + Sync,
+    R: Send,
+{
+    let f = &f;
+    thread::scope(|s| {
+        let handles: Vec<_> = chunks(space.len(), threads)
+            .map(|c| s.spawn(move || c.map(|i| f(space.get(i))).collect::<Vec<R>>()))
+            .collect();
+        let mut r = Vec::with_capacity(space.len());
+        for h in handles {
+            r.extend(h.join().unwrap());
+        }
+        r
+    })
+}
+
+pub fn par_map_collect<I, F, R>(space: &I, f: F) -> Vec<R>
+where
+    I: Indexing + Sync + ?Sized,
+    F: Fn(I::Item) -> R + Sync,
+    R: Send,
+{
+    par_map_collect_with(space, default_threads(), f)
+}
+
+/// Hands out the items of a space (typically a `Shuffled` one) to any number of threads. Claiming is a single atomic operation and the items are produced by the claiming thread with `get`, so there's no lock to serialize on.
+/// ```rust
+/// let cursor = AtomicCursor::new(light_shuffle(Cross(0..100, 0..100)));
+/// std::thread::scope(|s| {
+///     for _ in 0..8 {
+///         s.spawn(|| {
+///             while let Some((_, (a, b))) = cursor.claim() {
+///                 evaluate(a, b);
+///                 cursor.complete(1);
+///             }
+///         });
+///     }
+/// });
+/// assert!(cursor.is_done());
+/// ```
+pub struct AtomicCursor<I> {
+    space: I,
+    len: usize,
+    next: AtomicUsize,
+    completed: AtomicUsize,
+}
+impl<I: Indexing> AtomicCursor<I> {
+    pub fn new(space: I) -> Self {
+        Self::starting_at(space, 0)
+    }
+    /// treats everything before `at` as already claimed and completed, for resuming
+    pub fn starting_at(space: I, at: usize) -> Self {
+        let len = space.len();
+        let at = at.min(len);
+        Self {
+            space,
+            len,
+            next: AtomicUsize::new(at),
+            completed: AtomicUsize::new(at),
+        }
+    }
+    pub fn space(&self) -> &I {
+        &self.space
+    }
+    /// claims up to `n` consecutive indices. Empty once everything has been claimed.
+    pub fn claim_range(&self, n: usize) -> Range<usize> {
+        let len = self.len;
+        match self
+            .next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| {
+                (c < len).then(|| c + n.min(len - c))
+            }) {
+            Ok(start) => start..start + n.min(len - start),
+            Err(_) => len..len,
+        }
+    }
+    /// the next unclaimed index and its item
+    pub fn claim(&self) -> Option<(usize, I::Item)> {
+        let r = self.claim_range(1);
+        (!r.is_empty()).then(|| (r.start, self.space.get(r.start)))
+    }
+    /// claims up to `n` items at once, which is cheaper when items are quick to process
+    pub fn claim_batch(
+        &self,
+        n: usize,
+    ) -> impl ExactSizeIterator<Item = (usize, I::Item)> + '_ {
+        self.claim_range(n).map(|i| (i, self.space.get(i)))
+    }
+    /// records that `n` claimed items have been finished
+    pub fn complete(&self, n: usize) {
+        self.completed.fetch_add(n, Ordering::Release);
+    }
+    pub fn claimed(&self) -> usize {
+        self.next.load(Ordering::Relaxed)
+    }
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::Acquire)
+    }
+    /// everything has been claimed, though maybe not completed
+    pub fn is_exhausted(&self) -> bool {
+        self.claimed() >= self.len
+    }
+    /// everything has been completed
+    pub fn is_done(&self) -> bool {
+        self.completed() >= self.len
+    }
+}
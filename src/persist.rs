@@ -0,0 +1,452 @@
+//! A small stable text format for saving spaces, shufflers and cursors, so that long running jobs can be stopped and resumed with exactly the same order.
+//!
+//! Everything saved starts with a format version, and every value is preceded by a tag naming its type, so restoring into the wrong type (say, a space shuffled with a different shuffler) fails loudly instead of silently reordering everything. Shufflers are saved with all of their parameters rather than being rebuilt from the length, so a restore reproduces the saved permutation even if `for_length` changes in some later version.
+//! ```rust
+//! use mako_infinite_shuffle::{light_shuffle, persist, rng::DefaultShuffler, Cross, Indexing, IndexingIter, Shuffled};
+//! use std::ops::Range;
+//! let d = light_shuffle(Cross(0..30, 0..20));
+//! let mut it = d.into_iter();
+//! it.next();
+//! let saved = persist::save(&it);
+//! let restored: IndexingIter<Shuffled<Cross<Range<usize>, Range<usize>>, DefaultShuffler>, _> = persist::load(&saved).unwrap();
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    io,
+    ops::Range,
+    path::Path,
+};
+
+use crate::{
+    rng::{DynShuffler, Rng, Seeded, Shuffler, LFSRF, LFSRFNTimes},
+    Cross, Indexing, IndexVec, IndexingIter, KSubmultisets, KSubsets, Series, Shuffled, Truncate,
+};
+
+/// bumped whenever the meaning of a saved value changes
+pub const FORMAT_VERSION: u64 = 1;
+const MAGIC: &str = "mako_infinite_shuffle";
+
+#[derive(Debug)]
+pub enum PersistError {
+    Io(io::Error),
+    /// saved by a different version of the format
+    Version(u64),
+    /// the saved value was of a different type, or used a different shuffler
+    Tag {
+        expected: &'static str,
+        found: String,
+    },
+    Malformed(String),
+}
+impl Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(e) => write!(f, "{e}"),
+            PersistError::Version(v) => write!(
+                f,
+                "saved with format version {v}, but this is version {FORMAT_VERSION}"
+            ),
+            PersistError::Tag { expected, found } => {
+                write!(f, "expected a saved {expected}, found {found:?}")
+            }
+            PersistError::Malformed(m) => write!(f, "malformed save: {m}"),
+        }
+    }
+}
+impl std::error::Error for PersistError {}
+impl From<io::Error> for PersistError {
+    fn from(e: io::Error) -> Self {
+        PersistError::Io(e)
+    }
+}
+
+/// Things that can be saved in the format. Implementors write and read their fields through `Writer::value` and `Reader::value`, which take care of the tags.
+pub trait Persist: Sized {
+    /// names the type in the save. Empty for primitives, which don't need checking.
+    const TAG: &'static str;
+    fn write_fields(&self, w: &mut Writer);
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError>;
+}
+
+pub struct Writer {
+    out: String,
+}
+impl Writer {
+    fn token(&mut self, t: &str) {
+        if !self.out.is_empty() {
+            self.out.push(' ');
+        }
+        self.out.push_str(t);
+    }
+    pub fn value<T: Persist>(&mut self, v: &T) {
+        if !T::TAG.is_empty() {
+            self.token(T::TAG);
+        }
+        v.write_fields(self);
+    }
+}
+
+pub struct Reader<'a> {
+    rest: &'a str,
+}
+impl<'a> Reader<'a> {
+    fn token(&mut self) -> Result<&'a str, PersistError> {
+        let s = self.rest.trim_start_matches(' ');
+        let end = s.find(' ').unwrap_or(s.len());
+        if end == 0 {
+            return Err(PersistError::Malformed("ended early".into()));
+        }
+        self.rest = &s[end..];
+        Ok(&s[..end])
+    }
+    fn peek_token(&self) -> Result<&'a str, PersistError> {
+        Reader { rest: self.rest }.token()
+    }
+    fn number(&mut self) -> Result<u64, PersistError> {
+        let t = self.token()?;
+        t.parse()
+            .map_err(|_| PersistError::Malformed(format!("expected a number, found {t:?}")))
+    }
+    fn expect_tag(&mut self, expected: &'static str) -> Result<(), PersistError> {
+        let found = self.token()?;
+        if found == expected {
+            Ok(())
+        } else {
+            Err(PersistError::Tag {
+                expected,
+                found: found.into(),
+            })
+        }
+    }
+    pub fn value<T: Persist>(&mut self) -> Result<T, PersistError> {
+        if !T::TAG.is_empty() {
+            self.expect_tag(T::TAG)?;
+        }
+        T::read_fields(self)
+    }
+}
+
+fn malformed<T>(m: &str) -> Result<T, PersistError> {
+    Err(PersistError::Malformed(m.into()))
+}
+
+pub fn save<T: Persist>(v: &T) -> String {
+    let mut w = Writer { out: String::new() };
+    w.token(MAGIC);
+    w.token(&FORMAT_VERSION.to_string());
+    w.value(v);
+    w.out
+}
+
+pub fn load<T: Persist>(s: &str) -> Result<T, PersistError> {
+    let mut r = Reader {
+        rest: s.strip_suffix('\n').unwrap_or(s),
+    };
+    if r.token()? != MAGIC {
+        return malformed("not a mako_infinite_shuffle save");
+    }
+    let version = r.number()?;
+    if version != FORMAT_VERSION {
+        return Err(PersistError::Version(version));
+    }
+    let v = r.value()?;
+    if !r.rest.trim_start_matches(' ').is_empty() {
+        return malformed("trailing data");
+    }
+    Ok(v)
+}
+
+/// writes to a temporary file first and then moves it into place, so a crash mid-save never leaves a truncated save behind
+pub fn save_to_file<T: Persist>(v: &T, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, save(v))?;
+    std::fs::rename(&tmp, path)
+}
+
+pub fn load_from_file<T: Persist>(path: impl AsRef<Path>) -> Result<T, PersistError> {
+    load(&std::fs::read_to_string(path)?)
+}
+
+impl Persist for u64 {
+    const TAG: &'static str = "";
+    fn write_fields(&self, w: &mut Writer) {
+        w.token(&self.to_string());
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        r.number()
+    }
+}
+impl Persist for u32 {
+    const TAG: &'static str = "";
+    fn write_fields(&self, w: &mut Writer) {
+        w.token(&self.to_string());
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        r.number()?
+            .try_into()
+            .map_err(|_| PersistError::Malformed("number out of range".into()))
+    }
+}
+impl Persist for usize {
+    const TAG: &'static str = "";
+    fn write_fields(&self, w: &mut Writer) {
+        w.token(&self.to_string());
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        r.number()?
+            .try_into()
+            .map_err(|_| PersistError::Malformed("number out of range".into()))
+    }
+}
+/// written as the byte length, a colon, then the string itself, so it may contain spaces and newlines
+impl Persist for String {
+    const TAG: &'static str = "";
+    fn write_fields(&self, w: &mut Writer) {
+        w.token(&format!("{}:{}", self.len(), self));
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let s = r.rest.trim_start_matches(' ');
+        let Some((l, body)) = s.split_once(':') else {
+            return malformed("expected a string");
+        };
+        let l: usize = l
+            .parse()
+            .map_err(|_| PersistError::Malformed(format!("bad string length {l:?}")))?;
+        if body.len() < l || !body.is_char_boundary(l) {
+            return malformed("string ended early");
+        }
+        r.rest = &body[l..];
+        Ok(body[..l].to_string())
+    }
+}
+impl<T: Persist> Persist for Vec<T> {
+    const TAG: &'static str = "";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.len());
+        for e in self.iter() {
+            w.value(e);
+        }
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let l: usize = r.value()?;
+        (0..l).map(|_| r.value()).collect()
+    }
+}
+
+/// written as 0, or 1 then the value
+impl<T: Persist> Persist for Option<T> {
+    const TAG: &'static str = "";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&(self.is_some() as usize));
+        if let Some(v) = self {
+            w.value(v);
+        }
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        match r.value::<usize>()? {
+            0 => Ok(None),
+            1 => Ok(Some(r.value()?)),
+            _ => malformed("expected 0 or 1 for an option"),
+        }
+    }
+}
+
+impl Persist for LFSRF {
+    const TAG: &'static str = "LFSRF";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.taps);
+        w.value(&self.size);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let taps = r.value()?;
+        let size = r.value()?;
+        if !(1..=32).contains(&size) {
+            return malformed("lfsr size out of range");
+        }
+        // stepping backwards needs the lowest tap
+        if taps & 1 == 0 || (taps as u64) >> size != 0 {
+            return malformed("lfsr taps don't fit its size");
+        }
+        Ok(LFSRF { taps, size })
+    }
+}
+impl Persist for LFSRFNTimes {
+    const TAG: &'static str = "LFSRFNTimes";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.0);
+        w.value(&self.1);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(LFSRFNTimes(r.value()?, r.value()?))
+    }
+}
+impl<S: Persist> Persist for Seeded<S> {
+    const TAG: &'static str = "Seeded";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.seed);
+        w.value(&self.inner);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(Seeded {
+            seed: r.value()?,
+            inner: r.value()?,
+        })
+    }
+}
+impl Persist for DynShuffler {
+    const TAG: &'static str = "DynShuffler";
+    fn write_fields(&self, w: &mut Writer) {
+        match self {
+            DynShuffler::LFSRF(s) => w.value(s),
+            DynShuffler::LFSRFNTimes(s) => w.value(s),
+        }
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        match r.peek_token()? {
+            LFSRF::TAG => Ok(DynShuffler::LFSRF(r.value()?)),
+            LFSRFNTimes::TAG => Ok(DynShuffler::LFSRFNTimes(r.value()?)),
+            found => Err(PersistError::Tag {
+                expected: "shuffler",
+                found: found.into(),
+            }),
+        }
+    }
+}
+impl<Core: Persist + Shuffler> Persist for Rng<Core> {
+    const TAG: &'static str = "Rng";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.core);
+        w.value(&self.length);
+        w.value(&self.state);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let core: Core = r.value()?;
+        let length = r.value()?;
+        let state: u64 = r.value()?;
+        // states needn't start at 0 (an lfsr's start at 1), so check the range before converting
+        let first = core.output_to_state(0);
+        if core.period() < length
+            || state < first
+            || state - first >= core.period()
+            || core.state_to_output(state) >= length
+        {
+            return malformed("rng state doesn't fit its shuffler");
+        }
+        Ok(Rng {
+            core,
+            length,
+            state,
+        })
+    }
+}
+
+impl Persist for Range<usize> {
+    const TAG: &'static str = "Range";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.start);
+        w.value(&self.end);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(r.value()?..r.value()?)
+    }
+}
+impl<A: Persist, B: Persist> Persist for Cross<A, B> {
+    const TAG: &'static str = "Cross";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.0);
+        w.value(&self.1);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(Cross(r.value()?, r.value()?))
+    }
+}
+impl<A: Persist, B: Persist> Persist for Series<A, B> {
+    const TAG: &'static str = "Series";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.0);
+        w.value(&self.1);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(Series(r.value()?, r.value()?))
+    }
+}
+impl Persist for KSubsets {
+    const TAG: &'static str = "KSubsets";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.n);
+        w.value(&self.k);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(KSubsets::new(r.value()?, r.value()?))
+    }
+}
+impl Persist for KSubmultisets {
+    const TAG: &'static str = "KSubmultisets";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.n);
+        w.value(&self.k);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(KSubmultisets::new(r.value()?, r.value()?))
+    }
+}
+impl<T: Persist> Persist for IndexVec<T> {
+    const TAG: &'static str = "IndexVec";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.0);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(IndexVec(r.value()?))
+    }
+}
+impl<I: Persist> Persist for Truncate<I> {
+    const TAG: &'static str = "Truncate";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.0);
+        w.value(&self.1);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        Ok(Truncate(r.value()?, r.value()?))
+    }
+}
+impl<D: Persist + Indexing, S: Persist + Shuffler> Persist for Shuffled<D, S> {
+    const TAG: &'static str = "Shuffled";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.v);
+        w.value(&self.r);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let v: D = r.value()?;
+        let s: S = r.value()?;
+        if s.period() < v.len() as u64 {
+            return malformed("the shuffler's period is shorter than the space");
+        }
+        Ok(Shuffled { v, r: s })
+    }
+}
+/// the cursor of an owned iteration, as from `Indexing::into_iter`
+impl<D: Persist + Indexing> Persist for IndexingIter<D, D> {
+    const TAG: &'static str = "IndexingIter";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.at);
+        w.value(&self.len);
+        w.value(&self.v);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let at = r.value()?;
+        let len = r.value()?;
+        let v: D = r.value()?;
+        if at > len || len > v.len() {
+            return malformed("cursor runs past the end of its space");
+        }
+        Ok(IndexingIter {
+            v,
+            at,
+            len,
+            _i: std::marker::PhantomData,
+        })
+    }
+}
@@ -0,0 +1,233 @@
+//! Crash recovery for long sweeps. Items in an `Indexing` have dense indices, so a bitset over `0..len` records what's been done in a bit per item, and a resumed sweep can skip finished stretches a word at a time.
+//! ```rust
+//! let space = light_shuffle(Cross(0..1000, 0..1000));
+//! let mut progress = Progress::load_for_space("sweep.progress", &space).unwrap_or_else(|_| Progress::for_space(&space));
+//! for (i, item) in progress.remaining(&space).collect::<Vec<_>>() {
+//!     run(item);
+//!     progress.mark_done(i);
+//!     if i % 1000 == 0 {
+//!         progress.save_to_file("sweep.progress").unwrap();
+//!     }
+//! }
+//! ```
+
+use std::{io, path::Path};
+
+use crate::{
+    persist::{self, Persist, PersistError, Reader, Writer},
+    Indexing,
+};
+
+/// Records which indices of a space are done, and which have been excluded (should be skipped without being done).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Progress {
+    len: usize,
+    /// a hash of the saved space, when made with `for_space`. The bits are positions in the space's order, so they only mean anything for the same space with the same seed and shuffler.
+    space: Option<u64>,
+    done: Vec<u64>,
+    excluded: Vec<u64>,
+    done_count: usize,
+    excluded_count: usize,
+    remaining_count: usize,
+}
+
+fn bit(v: &[u64], i: usize) -> bool {
+    v[i / 64] & (1 << (i % 64)) != 0
+}
+/// sets the bit, returning whether it was previously unset
+fn set(v: &mut [u64], i: usize) -> bool {
+    let was = bit(v, i);
+    v[i / 64] |= 1 << (i % 64);
+    !was
+}
+fn count(v: &[u64]) -> usize {
+    v.iter().map(|w| w.count_ones() as usize).sum()
+}
+/// fnv-1a of the space's save, which unlike std's hashers is stable between builds
+fn identity<I: Persist>(space: &I) -> u64 {
+    persist::save(space)
+        .bytes()
+        .fold(0xcbf29ce484222325, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+impl Progress {
+    pub fn new(len: usize) -> Self {
+        let words = len.div_ceil(64);
+        Self {
+            len,
+            space: None,
+            done: vec![0; words],
+            excluded: vec![0; words],
+            done_count: 0,
+            excluded_count: 0,
+            remaining_count: len,
+        }
+    }
+    /// remembers which space this is, so that `load_for_space` can refuse progress made on a different one
+    pub fn for_space<I: Indexing + Persist>(space: &I) -> Self {
+        Self {
+            space: Some(identity(space)),
+            ..Self::new(space.len())
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn settled(&self, i: usize) -> bool {
+        bit(&self.done, i) || bit(&self.excluded, i)
+    }
+    pub fn mark_done(&mut self, i: usize) {
+        assert!(i < self.len);
+        let settled = self.settled(i);
+        if set(&mut self.done, i) {
+            self.done_count += 1;
+            if !settled {
+                self.remaining_count -= 1;
+            }
+        }
+    }
+    pub fn exclude(&mut self, i: usize) {
+        assert!(i < self.len);
+        let settled = self.settled(i);
+        if set(&mut self.excluded, i) {
+            self.excluded_count += 1;
+            if !settled {
+                self.remaining_count -= 1;
+            }
+        }
+    }
+    pub fn is_done(&self, i: usize) -> bool {
+        bit(&self.done, i)
+    }
+    pub fn is_excluded(&self, i: usize) -> bool {
+        bit(&self.excluded, i)
+    }
+    pub fn is_remaining(&self, i: usize) -> bool {
+        !self.settled(i)
+    }
+    pub fn done_count(&self) -> usize {
+        self.done_count
+    }
+    pub fn excluded_count(&self) -> usize {
+        self.excluded_count
+    }
+    /// neither done nor excluded
+    pub fn remaining_count(&self) -> usize {
+        self.remaining_count
+    }
+    pub fn is_finished(&self) -> bool {
+        self.remaining_count == 0
+    }
+    /// the indices that are neither done nor excluded, in order. Skips settled stretches 64 at a time.
+    pub fn remaining_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let len = self.len;
+        self.done
+            .iter()
+            .zip(self.excluded.iter())
+            .enumerate()
+            .flat_map(move |(wi, (d, e))| {
+                let mut open = !(d | e);
+                std::iter::from_fn(move || {
+                    if open == 0 {
+                        return None;
+                    }
+                    let b = open.trailing_zeros() as usize;
+                    open &= open - 1;
+                    Some(wi * 64 + b)
+                })
+            })
+            .take_while(move |i| *i < len)
+    }
+    /// the items of `space` that remain, with their indices, in the space's order (so for a shuffled space, the shuffled order)
+    pub fn remaining<'a, I: Indexing + ?Sized>(
+        &'a self,
+        space: &'a I,
+    ) -> impl Iterator<Item = (usize, I::Item)> + 'a {
+        assert_eq!(space.len(), self.len, "this progress is for a space of a different length");
+        self.remaining_indices().map(|i| (i, space.get(i)))
+    }
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save_to_file(self, path)
+    }
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, PersistError> {
+        persist::load_from_file(path)
+    }
+    /// loads, failing if the saved progress was made by `for_space` on a different space, which includes the same space shuffled with a different seed or shuffler, or wasn't made by `for_space` at all
+    pub fn load_for_space<I: Indexing + Persist>(
+        path: impl AsRef<Path>,
+        space: &I,
+    ) -> Result<Self, PersistError> {
+        let p = Self::load_from_file(path)?;
+        if p.len != space.len() {
+            return Err(PersistError::Malformed(format!(
+                "progress was recorded for a space of length {}, this one has length {}",
+                p.len,
+                space.len()
+            )));
+        }
+        match p.space {
+            Some(id) if id == identity(space) => Ok(p),
+            Some(_) => Err(PersistError::Malformed(
+                "progress was recorded for a different space, or a different shuffle of it".into(),
+            )),
+            None => Err(PersistError::Malformed(
+                "progress wasn't made by for_space, so there's no telling which space it's for"
+                    .into(),
+            )),
+        }
+    }
+}
+
+fn write_bits(w: &mut Writer, v: &[u64]) {
+    let hex: String = v.iter().map(|word| format!("{word:016x}")).collect();
+    w.value(&hex);
+}
+fn read_bits(r: &mut Reader, words: usize) -> Result<Vec<u64>, PersistError> {
+    let hex: String = r.value()?;
+    if hex.len() != words * 16 {
+        return Err(PersistError::Malformed("bitset has the wrong length".into()));
+    }
+    (0..words)
+        .map(|i| {
+            u64::from_str_radix(&hex[i * 16..(i + 1) * 16], 16)
+                .map_err(|_| PersistError::Malformed("bad bitset".into()))
+        })
+        .collect()
+}
+
+/// the bitsets are written as hex, two bits of save per item
+impl Persist for Progress {
+    const TAG: &'static str = "Progress";
+    fn write_fields(&self, w: &mut Writer) {
+        w.value(&self.len);
+        w.value(&self.space);
+        write_bits(w, &self.done);
+        write_bits(w, &self.excluded);
+    }
+    fn read_fields(r: &mut Reader) -> Result<Self, PersistError> {
+        let len: usize = r.value()?;
+        let space = r.value()?;
+        let words = len.div_ceil(64);
+        let done = read_bits(r, words)?;
+        let excluded = read_bits(r, words)?;
+        let tail = len % 64;
+        if tail != 0 && (done[words - 1] | excluded[words - 1]) >> tail != 0 {
+            return Err(PersistError::Malformed("bits set past the end".into()));
+        }
+        let settled: Vec<u64> = done.iter().zip(excluded.iter()).map(|(d, e)| d | e).collect();
+        Ok(Progress {
+            len,
+            space,
+            done_count: count(&done),
+            excluded_count: count(&excluded),
+            remaining_count: len - count(&settled),
+            done,
+            excluded,
+        })
+    }
+}
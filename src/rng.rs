@@ -1,5 +1,7 @@
+use std::str::FromStr;
+
+/// The stepping half of a shuffler. Construction lives in `FromLength` so that this stays object-safe, which lets the algorithm be chosen at runtime (see `DynShuffler`) or used as a `Box<dyn Shuffler>`.
 pub trait Shuffler {
-    fn for_length(l: usize) -> Self;
     fn next(&self, prev: u64) -> u64;
     fn state_to_output(&self, state: u64) -> u64 {
         state
@@ -7,9 +9,101 @@ pub trait Shuffler {
     fn output_to_state(&self, state: u64) -> u64 {
         state
     }
-    fn initial_state(length: usize) -> u64 {
+    fn initial_state(&self, length: usize) -> u64 {
         0x2ab18f32a337u64 % length as u64
     }
+    /// the number of outputs the shuffler cycles through. Every output is below this, and a shuffler can only shuffle lengths up to this.
+    fn period(&self) -> u64;
+}
+
+/// For shufflers whose parameters are fully determined by the length they're shuffling, which is all of the ones known at compile time.
+pub trait FromLength {
+    /// for period l. Should return with a period above and close to l, but doesn't have to be l exactly (the point of full period is that we can just try again if we get one that's too long, and if you're close enough to the correct period you have a probabilistic guarantee that you wont have to try too many times).
+    fn for_length(l: usize) -> Self;
+}
+
+/// Shufflers that can also step backwards, which is what finding an item's position in a shuffle (`unpermute`) needs. Separate from `Shuffler` so that shufflers defined elsewhere don't have to provide it.
+pub trait InvertibleShuffler: Shuffler {
+    /// the inverse of `next`
+    fn prev(&self, next: u64) -> u64;
+}
+
+impl<S: Shuffler + ?Sized> Shuffler for Box<S> {
+    fn next(&self, prev: u64) -> u64 {
+        (**self).next(prev)
+    }
+    fn state_to_output(&self, state: u64) -> u64 {
+        (**self).state_to_output(state)
+    }
+    fn output_to_state(&self, state: u64) -> u64 {
+        (**self).output_to_state(state)
+    }
+    fn initial_state(&self, length: usize) -> u64 {
+        (**self).initial_state(length)
+    }
+    fn period(&self) -> u64 {
+        (**self).period()
+    }
+}
+impl<S: Shuffler + ?Sized> Shuffler for &S {
+    fn next(&self, prev: u64) -> u64 {
+        (**self).next(prev)
+    }
+    fn state_to_output(&self, state: u64) -> u64 {
+        (**self).state_to_output(state)
+    }
+    fn output_to_state(&self, state: u64) -> u64 {
+        (**self).output_to_state(state)
+    }
+    fn initial_state(&self, length: usize) -> u64 {
+        (**self).initial_state(length)
+    }
+    fn period(&self) -> u64 {
+        (**self).period()
+    }
+}
+
+impl<S: InvertibleShuffler + ?Sized> InvertibleShuffler for Box<S> {
+    fn prev(&self, next: u64) -> u64 {
+        (**self).prev(next)
+    }
+}
+impl<S: InvertibleShuffler + ?Sized> InvertibleShuffler for &S {
+    fn prev(&self, next: u64) -> u64 {
+        (**self).prev(next)
+    }
+}
+
+/// where in the shuffled order of a `length` sized space the element `at` goes. Walks the shuffler's cycle from `at` until it lands back inside the space, so for a full period shuffler this is a permutation of `0..length`.
+pub fn permute<S: Shuffler + ?Sized>(r: &S, length: u64, at: u64) -> u64 {
+    let mut n = r.output_to_state(at);
+    loop {
+        n = r.next(n);
+        if r.state_to_output(n) < length {
+            break;
+        }
+    }
+    r.state_to_output(n)
+}
+
+/// the inverse of `permute`: which element of the space lands at `at` in the shuffled order
+pub fn unpermute<S: InvertibleShuffler + ?Sized>(r: &S, length: u64, at: u64) -> u64 {
+    let mut n = r.output_to_state(at);
+    loop {
+        n = r.prev(n);
+        if r.state_to_output(n) < length {
+            break;
+        }
+    }
+    r.state_to_output(n)
+}
+
+/// a quick, well mixed hash of a u64. Not a shuffler, for when something needs a few random bits derived from a seed.
+pub fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
 }
 
 // pub struct Lcg { m:u64, c:u64, };
@@ -30,7 +124,7 @@ pub struct LFSRF {
     pub size: u32,
 }
 impl Shuffler for LFSRF {
-    fn initial_state(length: usize) -> u64 {
+    fn initial_state(&self, length: usize) -> u64 {
         let special_salt = 0x2ab18f32a337u64; //shrug
         let mut state = special_salt % length as u64;
         if state == 0 {
@@ -42,15 +136,6 @@ impl Shuffler for LFSRF {
         //inspired by https://holzhaus.github.io/vinylla/src/vinylla/lfsr.rs.html#172
         (((prev & self.taps as u64).count_ones() as u64 & 1) << (self.size - 1)) | (prev >> 1)
     }
-    /// for period l. Should return with a period above and close to l, but doesn't have to be l exactly (the point of full period is that we can just try again if we get one that's too long, and if you're close enough to the correct period you have a probabilistic guarantee that you wont have to try too many times).
-    fn for_length(l: usize) -> Self {
-        // + 1 because a lfsr actually skips the 0
-        let bl = (l + 1).ilog2() + 1;
-        Self {
-            taps: TAPS[(bl - 1) as usize],
-            size: bl as u32,
-        }
-    }
     fn state_to_output(&self, state: u64) -> u64 {
         //a lfsr never generates 0
         state - 1
@@ -59,13 +144,40 @@ impl Shuffler for LFSRF {
         //a lfsr never generates 0
         state + 1
     }
+    fn period(&self) -> u64 {
+        (1 << self.size) - 1
+    }
+}
+impl InvertibleShuffler for LFSRF {
+    fn prev(&self, next: u64) -> u64 {
+        // the bit that was shifted out is whichever one gives the parity that was shifted in. Relies on the lowest tap being set, which it is for every entry of TAPS.
+        let top = next >> (self.size - 1);
+        let rest = (next << 1) & ((1 << self.size) - 1);
+        rest | (top ^ ((rest & self.taps as u64).count_ones() as u64 & 1))
+    }
+}
+
+/// the longest space the lfsr shufflers can shuffle: the widest register in the tap table is 32 bits, and an lfsr skips 0
+pub const MAX_LENGTH: usize = u32::MAX as usize - 1;
+
+impl FromLength for LFSRF {
+    fn for_length(l: usize) -> Self {
+        assert!(l <= MAX_LENGTH, "lfsrs can shuffle at most {MAX_LENGTH} items, not {l}");
+        // + 1 because a lfsr actually skips the 0
+        let bl = (l + 1).ilog2() + 1;
+        Self {
+            taps: TAPS[(bl - 1) as usize],
+            size: bl,
+        }
+    }
 }
 
 /// Just runs LFSRF with multiple iterations per next, which produces much more random output. The default iteration count specified in `for_length` is 11.
+#[derive(Clone, Copy)]
 pub struct LFSRFNTimes(pub usize, pub LFSRF);
 impl Shuffler for LFSRFNTimes {
-    fn initial_state(length: usize) -> u64 {
-        LFSRF::initial_state(length)
+    fn initial_state(&self, length: usize) -> u64 {
+        self.1.initial_state(length)
     }
     fn next(&self, prev: u64) -> u64 {
         let mut cur = prev;
@@ -74,19 +186,174 @@ impl Shuffler for LFSRFNTimes {
         }
         cur
     }
-    fn for_length(l: usize) -> Self {
-        Self(11, LFSRF::for_length(l))
-    }
     fn output_to_state(&self, state: u64) -> u64 {
         self.1.output_to_state(state)
     }
     fn state_to_output(&self, state: u64) -> u64 {
         self.1.state_to_output(state)
     }
+    fn period(&self) -> u64 {
+        self.1.period()
+    }
+}
+impl InvertibleShuffler for LFSRFNTimes {
+    fn prev(&self, next: u64) -> u64 {
+        let mut cur = next;
+        for _ in 0..self.0 {
+            cur = self.1.prev(cur);
+        }
+        cur
+    }
+}
+impl FromLength for LFSRFNTimes {
+    fn for_length(l: usize) -> Self {
+        Self(11, LFSRF::for_length(l))
+    }
 }
 
 pub type DefaultShuffler = LFSRFNTimes;
 
+/// Gives any shuffler a seed. Steps are conjugated by a rotation of the outputs by `seed` places, which keeps the cycle structure of `inner` (so a full period shuffler stays full period) while changing which stretch of the cycle falls inside a given length, so each seed produces an unrelated order.
+#[derive(Clone, Copy)]
+pub struct Seeded<S> {
+    pub seed: u64,
+    pub inner: S,
+}
+impl<S: Shuffler> Shuffler for Seeded<S> {
+    fn next(&self, prev: u64) -> u64 {
+        let p = self.inner.period();
+        let s = self.seed % p;
+        let rotated = self
+            .inner
+            .output_to_state((self.inner.state_to_output(prev) + s) % p);
+        let n = self.inner.next(rotated);
+        self.inner
+            .output_to_state((self.inner.state_to_output(n) + p - s) % p)
+    }
+    fn state_to_output(&self, state: u64) -> u64 {
+        self.inner.state_to_output(state)
+    }
+    fn output_to_state(&self, state: u64) -> u64 {
+        self.inner.output_to_state(state)
+    }
+    fn initial_state(&self, length: usize) -> u64 {
+        self.inner.initial_state(length)
+    }
+    fn period(&self) -> u64 {
+        self.inner.period()
+    }
+}
+impl<S: InvertibleShuffler> InvertibleShuffler for Seeded<S> {
+    fn prev(&self, next: u64) -> u64 {
+        let p = self.inner.period();
+        let s = self.seed % p;
+        let rotated = self
+            .inner
+            .output_to_state((self.inner.state_to_output(next) + s) % p);
+        let n = self.inner.prev(rotated);
+        self.inner
+            .output_to_state((self.inner.state_to_output(n) + p - s) % p)
+    }
+}
+
+/// Names the shufflers that can be chosen at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShufflerKind {
+    LFSRF,
+    LFSRFNTimes,
+}
+impl FromStr for ShufflerKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lfsrf" => Ok(ShufflerKind::LFSRF),
+            "lfsrf_n_times" => Ok(ShufflerKind::LFSRFNTimes),
+            _ => Err(format!(
+                "unknown shuffler {s:?}, expected \"lfsrf\" or \"lfsrf_n_times\""
+            )),
+        }
+    }
+}
+
+/// A shuffler whose algorithm was chosen at runtime, usually by a `ShufflerConfig`.
+#[derive(Clone, Copy)]
+pub enum DynShuffler {
+    LFSRF(LFSRF),
+    LFSRFNTimes(LFSRFNTimes),
+}
+impl Shuffler for DynShuffler {
+    fn next(&self, prev: u64) -> u64 {
+        match self {
+            DynShuffler::LFSRF(s) => s.next(prev),
+            DynShuffler::LFSRFNTimes(s) => s.next(prev),
+        }
+    }
+    fn state_to_output(&self, state: u64) -> u64 {
+        match self {
+            DynShuffler::LFSRF(s) => s.state_to_output(state),
+            DynShuffler::LFSRFNTimes(s) => s.state_to_output(state),
+        }
+    }
+    fn output_to_state(&self, state: u64) -> u64 {
+        match self {
+            DynShuffler::LFSRF(s) => s.output_to_state(state),
+            DynShuffler::LFSRFNTimes(s) => s.output_to_state(state),
+        }
+    }
+    fn initial_state(&self, length: usize) -> u64 {
+        match self {
+            DynShuffler::LFSRF(s) => s.initial_state(length),
+            DynShuffler::LFSRFNTimes(s) => s.initial_state(length),
+        }
+    }
+    fn period(&self) -> u64 {
+        match self {
+            DynShuffler::LFSRF(s) => s.period(),
+            DynShuffler::LFSRFNTimes(s) => s.period(),
+        }
+    }
+}
+impl InvertibleShuffler for DynShuffler {
+    fn prev(&self, next: u64) -> u64 {
+        match self {
+            DynShuffler::LFSRF(s) => s.prev(next),
+            DynShuffler::LFSRFNTimes(s) => s.prev(next),
+        }
+    }
+}
+
+/// Everything needed to build a shuffler at runtime, eg, from a config file. `rounds` is ignored by `ShufflerKind::LFSRF`, which always does one. Any `rounds` gives a valid `Shuffled`, but `Rng` needs a full period, which you only get when `rounds` shares no factors with the lfsr's period of `2^k - 1` (powers of two are always fine).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShufflerConfig {
+    pub kind: ShufflerKind,
+    pub rounds: usize,
+    pub seed: u64,
+}
+impl Default for ShufflerConfig {
+    /// the same shuffler as `DefaultShuffler`, unseeded
+    fn default() -> Self {
+        Self {
+            kind: ShufflerKind::LFSRFNTimes,
+            rounds: 11,
+            seed: 0,
+        }
+    }
+}
+impl ShufflerConfig {
+    pub fn for_length(&self, l: usize) -> Seeded<DynShuffler> {
+        let inner = match self.kind {
+            ShufflerKind::LFSRF => DynShuffler::LFSRF(LFSRF::for_length(l)),
+            ShufflerKind::LFSRFNTimes => {
+                DynShuffler::LFSRFNTimes(LFSRFNTimes(self.rounds, LFSRF::for_length(l)))
+            }
+        };
+        Seeded {
+            seed: self.seed,
+            inner,
+        }
+    }
+}
+
 // /// (Linear Congruential Generator)
 // #[derive(Clone, Copy)]
 // pub struct LCG {
@@ -192,13 +459,21 @@ const TAPS: [u32; 32] = [
 // }
 
 impl<Core: Shuffler> Rng<Core> {
-    pub fn for_length(length: usize) -> Self {
+    /// `core` must have a period of at least `length`
+    pub fn new(core: Core, length: usize) -> Self {
         Self {
-            core: Core::for_length(length),
+            state: core.initial_state(length),
+            core,
             length: length as u64,
-            state: Core::initial_state(length),
         }
     }
+    pub fn for_length(length: usize) -> Self
+    where
+        Core: FromLength,
+    {
+        Self::new(Core::for_length(length), length)
+    }
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> u64 {
         let r = self.state;
         // shouldn't loop long, as each iteration has an uncorrelated probability of being below range, for most shufflers it's better odds than a coin flip each time. Shufflers should have a full period (and are tested) so looping forever should be impossible.
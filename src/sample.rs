@@ -0,0 +1,62 @@
+//! Random picks from a space, for when you don't need to visit the whole thing. `sample` picks with replacement, `sample_distinct` without.
+//! ```rust
+//! let mut state = 42;
+//! let one = sample(&Cross(0..1000, KSubsets::new(40, 3)), &mut state);
+//! let ten_different = sample_distinct(&IndexVec(names), 10, 42);
+//! ```
+
+use std::collections::HashSet;
+
+use crate::{
+    rng::{self, ShufflerConfig},
+    Indexing, Shuffled,
+};
+
+/// a step of splitmix64, the state just counts up
+fn next_random(state: &mut u64) -> u64 {
+    let r = rng::splitmix64(*state);
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    r
+}
+
+/// a uniform index below `len`, by taking the high half of a 128 bit product, which is biased by at most len / 2^64
+fn random_below(state: &mut u64, len: usize) -> usize {
+    ((next_random(state) as u128 * len as u128) >> 64) as usize
+}
+
+/// A uniformly random item of the space, advancing `state`. The same state always gives the same item, so store it to resume. Panics if the space is empty.
+pub fn sample<I: Indexing + ?Sized>(space: &I, state: &mut u64) -> I::Item {
+    assert!(!space.is_empty(), "can't sample from an empty space");
+    space.get(random_below(state, space.len()))
+}
+
+/// An endless stream of uniformly random items of the space, with replacement
+pub fn samples<I: Indexing + ?Sized>(space: &I, seed: u64) -> impl Iterator<Item = I::Item> + '_ {
+    let mut state = seed;
+    std::iter::repeat_with(move || sample(space, &mut state))
+}
+
+/// k different items of the space (all of them if there aren't k), chosen by the seed. When k is small next to the length of the space, this uses Floyd's algorithm, which picks a uniformly random subset and stops after k hash insertions. Otherwise it takes the first k of a seeded shuffle, which is cheaper than hashing for large k but only as random as the shuffler, unless the space is longer than the shufflers go (`rng::MAX_LENGTH`), where it's Floyd's regardless. Either way, the order the items come in isn't meaningful.
+pub fn sample_distinct<I: Indexing + ?Sized>(space: &I, k: usize, seed: u64) -> Vec<I::Item> {
+    let len = space.len();
+    let k = k.min(len);
+    if k.saturating_mul(16) <= len || len > rng::MAX_LENGTH {
+        let mut state = seed;
+        let mut chosen = HashSet::with_capacity(k);
+        let mut order = Vec::with_capacity(k);
+        for j in len - k..len {
+            let t = random_below(&mut state, j + 1);
+            let pick = if chosen.insert(t) { t } else { j };
+            chosen.insert(pick);
+            order.push(pick);
+        }
+        order.into_iter().map(|i| space.get(i)).collect()
+    } else {
+        let config = ShufflerConfig {
+            seed,
+            ..ShufflerConfig::default()
+        };
+        let shuffled = Shuffled::with_config(space, &config);
+        (0..k).map(|i| shuffled.get(i)).collect()
+    }
+}
@@ -0,0 +1,158 @@
+//! Orders where some items should come up more than others.
+//!
+//! Items with the same weight form a class, and the output is a schedule of classes, spread with `ProportionalInterleave`'s ranking, where each class cycles through its members in a shuffled order. So an item's repeats are evenly spaced, and every item with any weight at all is guaranteed to come up. Weights are integers, floats get quantized down to a few levels (`Weighted::quantized`), which keeps the number of classes, and so the cost of a `get`, small.
+//! ```rust
+//! let loot = Weighted::quantized(IndexVec(items), |item| item.drop_rate, 100);
+//! for item in loot.iter().take(1000) {
+//!     place(item);
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::{
+    infinite::InfiniteIndexing,
+    proportional_unrank,
+    rng::{self, ShufflerConfig},
+    Indexing, InverseIndexing,
+};
+
+/// An infinite stream of the items of a space where each item comes up at a rate proportional to its weight. Every `period()` outputs, each item has come up exactly as many times as its weight. Construction evaluates the weight of every item.
+pub struct Weighted<I> {
+    v: I,
+    /// (weight, indices of the items with that weight), lightest first
+    classes: Vec<(u64, Vec<usize>)>,
+    /// how many outputs each class gets per period
+    shares: Vec<usize>,
+    config: ShufflerConfig,
+}
+impl<I> Weighted<I>
+where
+    I: Indexing,
+{
+    /// items of weight 0 never come up. Panics if every weight is 0.
+    pub fn new(v: I, weight: impl Fn(&I::Item) -> u64) -> Self {
+        let mut groups: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+        for i in 0..v.len() {
+            let w = weight(&v.get(i));
+            if w > 0 {
+                groups.entry(w).or_default().push(i);
+            }
+        }
+        assert!(!groups.is_empty(), "nothing has any weight");
+        let classes: Vec<(u64, Vec<usize>)> = groups.into_iter().collect();
+        let shares = classes
+            .iter()
+            .map(|(w, members)| *w as usize * members.len())
+            .collect();
+        Self {
+            v,
+            classes,
+            shares,
+            config: ShufflerConfig::default(),
+        }
+    }
+    /// for fractional weights. They're scaled so the heaviest is `levels` and rounded, except that anything with positive weight gets at least 1, so rare items stay rare but still come up. Negative weights count as 0.
+    pub fn quantized(v: I, weight: impl Fn(&I::Item) -> f64, levels: u64) -> Self {
+        let heaviest = (0..v.len()).map(|i| weight(&v.get(i))).fold(0.0, f64::max);
+        Self::new(v, |item| {
+            let w = weight(item);
+            if w > 0.0 {
+                ((w / heaviest * levels as f64).round() as u64).max(1)
+            } else {
+                0
+            }
+        })
+    }
+    /// shuffles each class's members with the algorithm, rounds and seed given in `config`
+    pub fn with_config(mut self, config: &ShufflerConfig) -> Self {
+        self.config = *config;
+        self
+    }
+    /// how many outputs it takes for every item to come up as many times as its weight
+    pub fn period(&self) -> usize {
+        self.shares.iter().sum()
+    }
+    /// how many distinct weights there are
+    pub fn class_count(&self) -> usize {
+        self.classes.len()
+    }
+    /// the index into the underlying space of the `at`th item
+    pub fn source_index(&self, at: u64) -> usize {
+        let period = self.period() as u64;
+        let (c, j) = proportional_unrank(&self.shares, period as usize, (at % period) as usize);
+        let (_, members) = &self.classes[c];
+        let n = members.len();
+        // this class's draws so far, each member coming up once every n draws
+        let draw = (at / period) as usize * self.shares[c] + j;
+        let shuffler = self.config.for_length(n);
+        members[rng::permute(&shuffler, n as u64, (draw % n) as u64) as usize]
+    }
+}
+impl<I> InfiniteIndexing for Weighted<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn get(&self, at: u64) -> Self::Item {
+        self.v.get(self.source_index(at))
+    }
+}
+
+/// Every item of a space exactly once, in a random order where heavier items tend to come earlier: the chance of any remaining item being next is proportional to its weight (Efraimidis and Spirakis' weighted sampling without replacement). Items of weight 0 go last. Sorts the whole space on construction, so it's for spaces small enough to hold an index table for.
+pub struct WeightedPermutation<I> {
+    v: I,
+    order: Vec<usize>,
+    /// where each index of the space is in `order`
+    positions: Vec<usize>,
+}
+impl<I> WeightedPermutation<I>
+where
+    I: Indexing,
+{
+    pub fn new(v: I, weight: impl Fn(&I::Item) -> f64, seed: u64) -> Self {
+        let keys: Vec<f64> = (0..v.len())
+            .map(|i| {
+                let w = weight(&v.get(i));
+                if w <= 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                // uniform in (0, 1], then u^(1/w), compared as logs so small weights don't underflow
+                let u = ((rng::splitmix64(seed ^ rng::splitmix64(i as u64)) >> 11) + 1) as f64
+                    / (1u64 << 53) as f64;
+                u.ln() / w
+            })
+            .collect();
+        let mut order: Vec<usize> = (0..v.len()).collect();
+        order.sort_by(|a, b| keys[*b].total_cmp(&keys[*a]).then(a.cmp(b)));
+        let mut positions = vec![0; order.len()];
+        for (p, i) in order.iter().enumerate() {
+            positions[*i] = p;
+        }
+        Self {
+            v,
+            order,
+            positions,
+        }
+    }
+}
+impl<I> Indexing for WeightedPermutation<I>
+where
+    I: Indexing,
+{
+    type Item = I::Item;
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+    fn get(&self, at: usize) -> Self::Item {
+        self.v.get(self.order[at])
+    }
+}
+impl<I> InverseIndexing for WeightedPermutation<I>
+where
+    I: InverseIndexing,
+{
+    fn index_of(&self, item: &I::Item) -> Option<usize> {
+        Some(self.positions[self.v.index_of(item)?])
+    }
+}